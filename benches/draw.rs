@@ -0,0 +1,102 @@
+//! Compares the frame buffer fast paths with drawing the same content pixel by pixel.
+//!
+//! Run with `cargo bench --bench draw`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+use embedded_graphics::{
+    image::{Image, ImageRaw, ImageRawBE},
+    pixelcolor::Rgb565,
+    prelude::*,
+    primitives::{Circle, PrimitiveStyle, Rectangle},
+};
+use ssd1351::display::display::Ssd1351;
+
+/// Display interface discarding everything, only the drawing is measured.
+struct NullInterface;
+
+impl WriteOnlyDataCommand for NullInterface {
+    fn send_commands(&mut self, _cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        Ok(())
+    }
+
+    fn send_data(&mut self, _buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        Ok(())
+    }
+}
+
+/// Draw target forwarding pixels one by one, as without the fast paths.
+struct PixelByPixel<'a, D>(&'a mut D);
+
+impl<D: DrawTarget> DrawTarget for PixelByPixel<'_, D> {
+    type Color = D::Color;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        self.0.draw_iter(pixels)
+    }
+}
+
+impl<D: DrawTarget> Dimensions for PixelByPixel<'_, D> {
+    fn bounding_box(&self) -> Rectangle {
+        self.0.bounding_box()
+    }
+}
+
+fn draw_rectangle<D: DrawTarget<Color = Rgb565>>(target: &mut D) {
+    let _ = Rectangle::new(Point::new(-8, 4), Size::new(120, 100))
+        .into_styled(PrimitiveStyle::with_fill(Rgb565::RED))
+        .draw(target);
+}
+
+fn draw_circle<D: DrawTarget<Color = Rgb565>>(target: &mut D) {
+    let _ = Circle::new(Point::new(4, 4), 120)
+        .into_styled(PrimitiveStyle::with_fill(Rgb565::GREEN))
+        .draw(target);
+}
+
+fn draw_image<D: DrawTarget<Color = Rgb565>>(target: &mut D, image: &ImageRawBE<'_, Rgb565>) {
+    let _ = Image::new(image, Point::new(-16, 8)).draw(target);
+}
+
+fn benchmark(c: &mut Criterion) {
+    let mut display = Ssd1351::new(NullInterface);
+    let data: Vec<u8> = (0..96 * 96 * 2).map(|i| i as u8).collect();
+    let image = ImageRaw::<Rgb565>::new(&data, 96);
+
+    let mut group = c.benchmark_group("filled rectangle");
+    group.bench_function("fill_solid", |b| b.iter(|| draw_rectangle(&mut display)));
+    group.bench_function("pixel by pixel", |b| {
+        b.iter(|| draw_rectangle(&mut PixelByPixel(&mut display)))
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("filled circle");
+    group.bench_function("fill_solid", |b| b.iter(|| draw_circle(&mut display)));
+    group.bench_function("pixel by pixel", |b| {
+        b.iter(|| draw_circle(&mut PixelByPixel(&mut display)))
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("ImageRaw blit");
+    group.bench_function("fill_contiguous", |b| {
+        b.iter(|| draw_image(&mut display, black_box(&image)))
+    });
+    group.bench_function("pixel by pixel", |b| {
+        b.iter(|| draw_image(&mut PixelByPixel(&mut display), black_box(&image)))
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("clear");
+    group.bench_function("clear", |b| b.iter(|| display.clear(Rgb565::BLUE)));
+    group.bench_function("pixel by pixel", |b| {
+        b.iter(|| PixelByPixel(&mut display).clear(Rgb565::BLUE))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, benchmark);
+criterion_main!(benches);
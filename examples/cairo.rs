@@ -7,7 +7,6 @@ use
         spi::{Bus, Mode, SlaveSelect, Spi},
     };
 
-use ssd1351;
 
 const DISPLAY_WIDTH: f64 = 128.0;
 const DISPLAY_HEIGHT: f64 = 128.0;
@@ -23,7 +22,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let  rst = gpio.get(25).unwrap().into_output();
 
     // Init the display
-    let mut disp = ssd1351::simple_display::display::Ssd1351::new(spi,dc,rst);
+    let mut disp = ssd1351::simple_display::display::Ssd1351::from_rppal(spi,dc,rst);
 
     // Reset & init
     disp.reset().unwrap();
@@ -9,11 +9,11 @@
 use chrono::{Local, Timelike};
 use core::f32::consts::PI;
 use {
-    display_interface_spi::SPIInterfaceNoCS,
-    linux_embedded_hal::Delay,
+    display_interface_spi::SPIInterface,
     rppal::{
         gpio::Gpio,
-        spi::{Bus, Mode, SlaveSelect, Spi},
+        hal::Delay,
+        spi::{Bus, Mode, SimpleHalSpiDevice, SlaveSelect, Spi},
     },
 };
 
@@ -29,7 +29,6 @@ use std::{thread, time::Duration};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use simple_signal::{self, Signal};
-use  ssd1351;
 
 /// The margin between the clock face and the display border.
 const MARGIN: u32 = 1;
@@ -150,7 +149,7 @@ where
 {
     // Create a styled text object for the time text.
     let mut text = Text::new(
-        &time_str,
+        time_str,
         Point::zero(),
         MonoTextStyle::new(&FONT_9X15, Rgb565::BLACK),
     );
@@ -196,17 +195,16 @@ fn main()  {
     let mut rst = gpio.get(25).unwrap().into_output();
 
     // Init SPI
-    let spii = SPIInterfaceNoCS::new(spi, dc);
+    let spii = SPIInterface::new(SimpleHalSpiDevice::new(spi), dc);
     let mut disp = ssd1351::display::display::Ssd1351::new(spii);
 
     // Reset & init
-    disp.reset(&mut rst, &mut Delay).unwrap();
+    disp.reset(&mut rst, &mut Delay::new()).unwrap();
     disp.turn_on().unwrap();
 
     // Clear the disp
     disp.clear(Rgb565::BLUE).unwrap();
-    //disp.flush().unwrap();
-    disp.flush();
+    disp.flush().unwrap();
 
     let clock_face = create_face(&disp);
 
@@ -227,26 +225,26 @@ fn main()  {
             time.second()
         );
 
-        disp.clear(Rgb565::BLACK);
+        disp.clear(Rgb565::BLACK).unwrap();
 
-        draw_face(&mut disp, &clock_face);
-        draw_hand(&mut disp, &clock_face, hours_radians, -35);
-        draw_hand(&mut disp, &clock_face, minutes_radians, -20);
-        draw_hand(&mut disp, &clock_face, seconds_radians, 0);
-        draw_second_decoration(&mut disp, &clock_face, seconds_radians, -20);
+        draw_face(&mut disp, &clock_face).unwrap();
+        draw_hand(&mut disp, &clock_face, hours_radians, -35).unwrap();
+        draw_hand(&mut disp, &clock_face, minutes_radians, -20).unwrap();
+        draw_hand(&mut disp, &clock_face, seconds_radians, 0).unwrap();
+        draw_second_decoration(&mut disp, &clock_face, seconds_radians, -20).unwrap();
 
         // Draw digital clock just above center.
-        draw_digital_clock(&mut disp, &clock_face, &digital_clock_text);
+        draw_digital_clock(&mut disp, &clock_face, &digital_clock_text).unwrap();
 
         // Draw a small circle over the hands in the center of the clock face.
         // This has to happen after the hands are drawn so they're covered up.
         Circle::with_center(clock_face.center(), 9)
             .into_styled(PrimitiveStyle::with_fill(Rgb565::WHITE))
-            .draw(&mut disp);
-        disp.flush();
+            .draw(&mut disp).unwrap();
+        disp.flush().unwrap();
 
         thread::sleep(Duration::from_millis(200));
     }
-    disp.reset(&mut rst, &mut Delay).unwrap();
     disp.turn_off().unwrap();
+    disp.reset(&mut rst, &mut Delay::new()).unwrap();
 }
@@ -1,9 +1,9 @@
 use {
-    display_interface_spi::SPIInterfaceNoCS,
-    linux_embedded_hal::Delay,
+    display_interface_spi::SPIInterface,
     rppal::{
         gpio::Gpio,
-        spi::{Bus, Mode, SlaveSelect, Spi},
+        hal::Delay,
+        spi::{Bus, Mode, SimpleHalSpiDevice, SlaveSelect, Spi},
     },
 };
 
@@ -14,7 +14,6 @@ use embedded_graphics::{
     text::{Alignment, Text},
 };
 use std::{thread, time::Duration};
-use ssd1351;
 
 fn main() {
     // Configure gpio
@@ -24,17 +23,16 @@ fn main() {
     let mut rst = gpio.get(25).unwrap().into_output();
 
     // Init SPI
-    let spii = SPIInterfaceNoCS::new(spi, dc);
+    let spii = SPIInterface::new(SimpleHalSpiDevice::new(spi), dc);
     let mut disp = ssd1351::display::display::Ssd1351::new(spii);
 
     // Reset & init
-    disp.reset(&mut rst, &mut Delay).unwrap();
+    disp.reset(&mut rst, &mut Delay::new()).unwrap();
     disp.turn_on().unwrap();
 
     // Clear the display
     disp.clear(Rgb565::BLUE).unwrap();
-    //disp.flush().unwrap();
-    disp.flush();
+    disp.flush().unwrap();
 
     //Write "Hello" to the display
     let character_style = MonoTextStyle::new(&FONT_10X20, Rgb565::RED);
@@ -50,10 +48,10 @@ fn main() {
     .draw(&mut disp)
     .unwrap();
 
-    disp.flush();
+    disp.flush().unwrap();
 
     thread::sleep(Duration::from_secs(30));
 
-    disp.reset(&mut rst, &mut Delay).unwrap();
     disp.turn_off().unwrap();
+    disp.reset(&mut rst, &mut Delay::new()).unwrap();
 }
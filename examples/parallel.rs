@@ -0,0 +1,69 @@
+use {
+    display_interface_parallel_gpio::{Generic8BitBus, PGPIO8BitInterface},
+    rppal::{gpio::Gpio, hal::Delay},
+};
+
+use embedded_graphics::{
+    mono_font::{ascii::FONT_10X20, MonoTextStyle},
+    pixelcolor::Rgb565,
+    prelude::*,
+    text::{Alignment, Text},
+};
+use ssd1351::display::{command::ParallelInterface, config::Config, display::Ssd1351};
+use std::{thread, time::Duration};
+
+fn main() {
+    // Configure gpio, the module is wired for the 8-bit 8080 interface (BS0 low, BS1 high)
+    let gpio = Gpio::new().unwrap();
+    let pin = |number| gpio.get(number).unwrap().into_output();
+    let bus = Generic8BitBus::new((
+        pin(4),
+        pin(5),
+        pin(6),
+        pin(12),
+        pin(13),
+        pin(16),
+        pin(17),
+        pin(22),
+    ));
+    let dc = pin(24);
+    let mut rst = pin(25);
+    // Chip select low and read strobe high for the whole session
+    let mut cs = pin(8);
+    cs.set_low();
+    let mut rd = pin(7);
+    rd.set_high();
+    let wr = pin(23);
+
+    // Init the parallel interface
+    let pi = PGPIO8BitInterface::new(bus, dc, wr);
+    let config = Config::default().parallel_interface(ParallelInterface::Bits8);
+    let mut disp: Ssd1351<_, 128, 128> = Ssd1351::with_config(pi, config);
+
+    // Reset & init
+    disp.reset(&mut rst, &mut Delay::new()).unwrap();
+    disp.turn_on().unwrap();
+
+    // Clear the display
+    disp.clear(Rgb565::BLUE).unwrap();
+    disp.flush().unwrap();
+
+    //Write "Parallel" to the display
+    let character_style = MonoTextStyle::new(&FONT_10X20, Rgb565::RED);
+
+    Text::with_alignment(
+        "Parallel",
+        disp.bounding_box().center(),
+        character_style,
+        Alignment::Center,
+    )
+    .draw(&mut disp)
+    .unwrap();
+
+    disp.flush().unwrap();
+
+    thread::sleep(Duration::from_secs(30));
+
+    disp.turn_off().unwrap();
+    disp.reset(&mut rst, &mut Delay::new()).unwrap();
+}
@@ -0,0 +1,367 @@
+//! Buffered display driver over an async display interface
+use crate::display::{
+    colour::ColourMode,
+    command::{Command, ScrollSpeed},
+    config::Config,
+    controller::{pixel_words, Controller, PixelFormat},
+    dirty::DirtyArea,
+    frame::Frame,
+    rotation::Rotation,
+    shadow::{FrameShadow, NoShadow, Shadow},
+    state::State,
+};
+use crate::Error;
+use core::ops::Range;
+use display_interface::{
+    AsyncWriteOnlyDataCommand,
+    DataFormat::{U16BEIter, U8},
+};
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    geometry::{OriginDimensions, Size},
+    pixelcolor::Rgb565,
+    primitives::Rectangle,
+    Pixel,
+};
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::delay::DelayNs;
+
+/// Represents the SSD1351 Display, driven through an async interface.
+///
+/// The counterpart of `display::Ssd1351`: drawing writes to the frame buffer, while `turn_on`,
+/// `reset`, `flush` and `flush_region` wait for the transfers without blocking the executor.
+///
+/// ```ignore
+/// let mut disp: Ssd1351Async<_, 128, 96> =
+///     Ssd1351Async::with_config(spii, Config::adafruit_128x96());
+/// disp.turn_on().await?;
+/// ```
+pub struct Ssd1351Async<
+    DI,
+    const WIDTH: usize = 128,
+    const HEIGHT: usize = 128,
+    S = NoShadow,
+    C: ColourMode = Rgb565,
+> {
+    display: DI,
+    controller: Controller<WIDTH, HEIGHT, Frame<WIDTH, HEIGHT, S, C>>,
+}
+
+impl<DI: AsyncWriteOnlyDataCommand> Ssd1351Async<DI> {
+    /// Creates the 128x128 SSD1351 Display.
+    ///
+    /// Make sure to reset and initialize the display before use!
+    pub fn new(display: DI) -> Self {
+        Self::with_config(display, Config::default())
+    }
+}
+
+impl<DI, const WIDTH: usize, const HEIGHT: usize, C> Ssd1351Async<DI, WIDTH, HEIGHT, NoShadow, C>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    C: ColourMode,
+{
+    /// Creates the SSD1351 Display with the settings applied by `turn_on`.
+    ///
+    /// Use one of the `Config` presets to match the module. Its geometry must be `WIDTH` x
+    /// `HEIGHT`, `turn_on` fails with `Error::InvalidArgument` otherwise. The colour depth is
+    /// replaced by the one of `C`.
+    ///
+    /// The geometry is not inferred from the configuration: name the type of the display, the
+    /// defaults of the type parameters only apply to an annotation.
+    ///
+    /// ```ignore
+    /// let mut disp: Ssd1351Async<_> = Ssd1351Async::with_config(spii, Config::adafruit_128x128());
+    /// ```
+    ///
+    /// Make sure to reset and initialize the display before use!
+    pub fn with_config(display: DI, config: Config) -> Self {
+        Self {
+            display,
+            controller: Controller::new::<C>(config, Frame::new(NoShadow, config.byte_order)),
+        }
+    }
+
+    /// Enables the frame diff flush, see `display::Ssd1351::with_frame_diff`.
+    pub fn with_frame_diff(
+        self,
+    ) -> Ssd1351Async<DI, WIDTH, HEIGHT, FrameShadow<WIDTH, HEIGHT, C>, C> {
+        Ssd1351Async {
+            display: self.display,
+            controller: self
+                .controller
+                .map_pixels(|frame| frame.with_shadow(FrameShadow::default())),
+        }
+    }
+}
+
+impl<DI, const WIDTH: usize, const HEIGHT: usize, S, C> Ssd1351Async<DI, WIDTH, HEIGHT, S, C>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    S: Shadow<C>,
+    C: ColourMode,
+{
+    /// Resets the display.
+    pub async fn reset<RST, DELAY>(
+        &mut self,
+        rst: &mut RST,
+        delay: &mut DELAY,
+    ) -> Result<(), Error<RST::Error>>
+    where
+        RST: OutputPin,
+        DELAY: DelayNs,
+    {
+        rst.set_low().map_err(Error::Pin)?;
+        delay.delay_ms(100).await;
+
+        rst.set_high().map_err(Error::Pin)?;
+        delay.delay_ms(100).await;
+
+        self.controller.reset();
+        Ok(())
+    }
+
+    /// Marks the display as reset, for boards without a reset pin.
+    ///
+    /// The controller resets itself at power on.
+    pub fn assume_reset(&mut self) {
+        self.controller.reset();
+    }
+
+    /// Initializes the display with the settings of its configuration.
+    ///
+    /// The display must be reset first. A display turned off is only turned back on, the GDDRAM
+    /// content is kept.
+    pub async fn turn_on(&mut self) -> Result<(), Error> {
+        send_commands(&mut self.display, self.controller.turn_on()?).await?;
+        self.controller.turned_on();
+        Ok(())
+    }
+
+    /// Returns the lifecycle state of the display.
+    pub fn state(&self) -> State {
+        self.controller.state
+    }
+
+    /// Rotates the image on the panel.
+    ///
+    /// The dimensions of the draw target are swapped by `Deg90` and `Deg270`, redraw the frame
+    /// buffer before the next flush.
+    pub async fn set_rotation(&mut self, rotation: Rotation) -> Result<(), Error> {
+        send_commands(&mut self.display, self.controller.set_rotation(rotation)).await
+    }
+
+    /// Mirrors the image horizontally and/or vertically, after the rotation.
+    pub async fn set_mirror(&mut self, horizontal: bool, vertical: bool) -> Result<(), Error> {
+        let commands = self.controller.set_mirror(horizontal, vertical);
+        send_commands(&mut self.display, commands).await
+    }
+
+    /// Returns the rotation of the image.
+    pub fn rotation(&self) -> Rotation {
+        self.controller.rotation()
+    }
+
+    /// Sets the brightness, see `display::Ssd1351::set_brightness`.
+    pub async fn set_brightness(&mut self, level: u8) -> Result<(), Error> {
+        let command = self.controller.set_brightness(level)?;
+        self.send_command(command).await
+    }
+
+    /// Sets the contrast current of the red, green and blue channels, see
+    /// `display::Ssd1351::set_channel_contrast`.
+    pub async fn set_channel_contrast(
+        &mut self,
+        red: u8,
+        green: u8,
+        blue: u8,
+    ) -> Result<(), Error> {
+        let command = self.controller.set_channel_contrast(red, green, blue)?;
+        self.send_command(command).await
+    }
+
+    /// Scrolls the image up by `offset` rows, see `display::Ssd1351::set_scroll_offset`.
+    pub async fn set_scroll_offset(&mut self, offset: u8) -> Result<(), Error> {
+        let command = self.controller.set_scroll_offset(offset)?;
+        self.send_command(command).await
+    }
+
+    /// Returns the rows the image is scrolled by.
+    pub fn scroll_offset(&self) -> u8 {
+        self.controller.scroll_offset()
+    }
+
+    /// Returns the frame buffer row shown at the row `row` of the scrolled image, or the column
+    /// when the rotation swaps the dimensions.
+    pub fn wrapped_row(&self, row: u32) -> u32 {
+        self.controller.wrapped_row(row)
+    }
+
+    /// Scrolls the image up by `rows`, and sends only the frame buffer rows exposed at the bottom,
+    /// see `display::Ssd1351::scroll_up`.
+    pub async fn scroll_up(&mut self, rows: u8) -> Result<(), Error> {
+        let (commands, windows) = self.controller.scroll_up(rows)?;
+        send_commands(&mut self.display, commands).await?;
+        self.controller.scroll_stopped();
+        for window in windows {
+            self.send_window(window).await?;
+        }
+        Ok(())
+    }
+
+    /// Turns off the display, the GDDRAM content is kept.
+    pub async fn turn_off(&mut self) -> Result<(), Error> {
+        let command = self.controller.turn_off()?;
+        self.send_command(command).await?;
+        self.controller.turned_off();
+        Ok(())
+    }
+
+    /// Starts the horizontal scroll of the panel `rows`, see
+    /// `display::Ssd1351::start_horizontal_scroll`.
+    pub async fn start_horizontal_scroll(
+        &mut self,
+        rows: Range<u8>,
+        step: u8,
+        speed: ScrollSpeed,
+    ) -> Result<(), Error> {
+        let commands = self.controller.start_horizontal_scroll(rows, step, speed)?;
+        send_commands(&mut self.display, commands).await?;
+        self.controller.scroll_started();
+        Ok(())
+    }
+
+    /// Stops the horizontal scroll.
+    ///
+    /// The scroll leaves the GDDRAM content undefined, the next flush sends the whole frame buffer.
+    pub async fn stop_scroll(&mut self) -> Result<(), Error> {
+        send_commands(&mut self.display, self.controller.stop_scroll()?).await?;
+        self.controller.scroll_stopped();
+        Ok(())
+    }
+
+    /// Returns true while the horizontal scroll runs.
+    pub fn is_scrolling(&self) -> bool {
+        self.controller.is_scrolling()
+    }
+
+    /// Allows to send custom commands to the display.
+    pub async fn send_command(&mut self, command: Command) -> Result<(), Error> {
+        command.send_async(&mut self.display).await
+    }
+
+    /// Flushes the display, and makes the output visible on the screen.
+    ///
+    /// Only the bounding box of the pixels drawn since the last flush is sent, or the changed spans
+    /// within it when the frame diff flush is enabled.
+    ///
+    /// A running horizontal scroll is stopped first, and the whole frame buffer is sent.
+    pub async fn flush(&mut self) -> Result<(), Error> {
+        send_commands(&mut self.display, self.controller.flush()?).await?;
+        self.controller.scroll_stopped();
+        let mut windows = self.controller.pixels.flush_windows();
+        while let Some(window) = self.controller.pixels.next_window(&mut windows) {
+            self.send_window(window).await?;
+        }
+        Ok(())
+    }
+
+    /// Flushes the part of the frame buffer within `area`, and makes it visible on the screen.
+    ///
+    /// The area is clipped to the display.
+    pub async fn flush_region(&mut self, area: Rectangle) -> Result<(), Error> {
+        let (commands, window) = self.controller.flush_region(&area)?;
+        send_commands(&mut self.display, commands).await?;
+        self.controller.scroll_stopped();
+        match window {
+            Some(window) => self.send_window(window).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Sends the pixels of the frame buffer within `window`.
+    async fn send_window(&mut self, window: DirtyArea) -> Result<(), Error> {
+        let format = self.controller.pixel_format()?;
+        send_commands(&mut self.display, self.controller.address_window(window)?).await?;
+        for bytes in self.controller.pixels.window_data(window) {
+            match format {
+                PixelFormat::Bytes => self.display.send_data(U8(bytes)).await?,
+                PixelFormat::Words => {
+                    let mut words = pixel_words(bytes);
+                    self.display.send_data(U16BEIter(&mut words)).await?
+                }
+            }
+        }
+        self.controller.pixels.window_sent(window);
+        Ok(())
+    }
+}
+
+/// Sends the commands in order.
+async fn send_commands<DI, I>(display: &mut DI, commands: I) -> Result<(), Error>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    I: IntoIterator<Item = Command>,
+{
+    for command in commands {
+        command.send_async(display).await?;
+    }
+    Ok(())
+}
+
+impl<DI, const WIDTH: usize, const HEIGHT: usize, S, C> DrawTarget
+    for Ssd1351Async<DI, WIDTH, HEIGHT, S, C>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    S: Shadow<C>,
+    C: ColourMode,
+{
+    type Color = C;
+    type Error = Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        self.controller
+            .pixels
+            .draw_iter(pixels)
+            .map_err(|never| match never {})
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        self.controller
+            .pixels
+            .fill_contiguous(area, colors)
+            .map_err(|never| match never {})
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.controller
+            .pixels
+            .fill_solid(area, color)
+            .map_err(|never| match never {})
+    }
+
+    fn clear(&mut self, fill: C) -> Result<(), Self::Error> {
+        self.controller
+            .pixels
+            .clear(fill)
+            .map_err(|never| match never {})
+    }
+}
+
+impl<DI, const WIDTH: usize, const HEIGHT: usize, S, C> OriginDimensions
+    for Ssd1351Async<DI, WIDTH, HEIGHT, S, C>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    S: Shadow<C>,
+    C: ColourMode,
+{
+    fn size(&self) -> Size {
+        self.controller.pixels.size()
+    }
+}
@@ -0,0 +1,77 @@
+//! Colour depths of the GDDRAM
+use crate::display::{
+    command::{ColourDepth, Remap},
+    config::ByteOrder,
+};
+use embedded_graphics::pixelcolor::{
+    raw::{RawData, RawU16},
+    PixelColor, Rgb565, Rgb666, RgbColor,
+};
+
+/// Colour type of the buffered drivers, which selects the colour depth of the GDDRAM.
+///
+/// - `Rgb565`, the default, selects the 65k colour mode with two bytes per pixel.
+/// - `Rgb666` selects the 262k colour mode with three bytes per pixel, one per channel, which
+///   reduces the banding of gradients. The pixels take half as long again to flush.
+///
+/// The colour depth of the re-map register set by `Config::remap` is replaced by the one of the
+/// colour type.
+pub trait ColourMode: PixelColor + private::Sealed {
+    /// Bytes of a pixel, in the order the GDDRAM expects them
+    #[doc(hidden)]
+    type Bytes: Copy + Default + Eq + core::fmt::Debug;
+
+    /// Colour depth of the re-map register
+    #[doc(hidden)]
+    const DEPTH: ColourDepth;
+
+    /// Returns the bytes of the colour, `byte_order` applies to the 16-bit colours.
+    #[doc(hidden)]
+    fn to_bytes(self, byte_order: ByteOrder) -> Self::Bytes;
+
+    /// Returns the bytes of the pixels.
+    #[doc(hidden)]
+    fn as_bytes(pixels: &[Self::Bytes]) -> &[u8];
+}
+
+impl ColourMode for Rgb565 {
+    type Bytes = [u8; 2];
+    const DEPTH: ColourDepth = ColourDepth::Colours65k;
+
+    fn to_bytes(self, byte_order: ByteOrder) -> Self::Bytes {
+        byte_order.bytes(RawU16::from(self).into_inner())
+    }
+
+    fn as_bytes(pixels: &[Self::Bytes]) -> &[u8] {
+        pixels.as_flattened()
+    }
+}
+
+impl ColourMode for Rgb666 {
+    type Bytes = [u8; 3];
+    const DEPTH: ColourDepth = ColourDepth::Colours262k;
+
+    fn to_bytes(self, _byte_order: ByteOrder) -> Self::Bytes {
+        // The 6 bits of each channel are the lowest bits of its byte
+        [self.r(), self.g(), self.b()]
+    }
+
+    fn as_bytes(pixels: &[Self::Bytes]) -> &[u8] {
+        pixels.as_flattened()
+    }
+}
+
+/// Returns the re-map setting `remap` with the colour depth of `C`.
+pub(crate) fn with_depth<C: ColourMode>(remap: Remap) -> Remap {
+    Remap {
+        colour_depth: C::DEPTH,
+        ..remap
+    }
+}
+
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for embedded_graphics::pixelcolor::Rgb565 {}
+    impl Sealed for embedded_graphics::pixelcolor::Rgb666 {}
+}
@@ -1,134 +1,520 @@
 //! Contains all the commands that can be sent to the display
 
-//use super::interface::DisplayInterface;
-use display_interface::{DataFormat::U8, DisplayError, WriteOnlyDataCommand};
+#[cfg(feature = "async")]
+use display_interface::AsyncWriteOnlyDataCommand;
+use crate::Error;
+use display_interface::{DataFormat::U8, WriteOnlyDataCommand};
+
+/// Number of entries of the gray scale look up table (0xB8).
+pub const GRAYSCALE_TABLE_LEN: usize = 63;
+
+/// Highest value accepted in the gray scale look up table (0xB8).
+const GRAYSCALE_MAX: u8 = 180;
+
+/// Display modes (0xA4 - 0xA7).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DisplayMode {
+    /// All pixels off (0xA4)
+    AllOff,
+    /// All pixels on at the highest gray scale level (0xA5)
+    AllOn,
+    /// Normal display, the output follows the GDDRAM (0xA6)
+    Normal,
+    /// Inverse display (0xA7)
+    Inverse,
+}
+
+/// Segment low voltage source (0xB4).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SegmentLowVoltage {
+    /// External VSL
+    External,
+    /// Internal VSL
+    Internal,
+}
+
+/// Command lock settings (0xFD).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommandLock {
+    /// Unlock the driver IC (0x12)
+    Unlock,
+    /// Lock the driver IC, only 0xFD is accepted (0x16)
+    Lock,
+    /// Make 0xA2, 0xB1, 0xB3, 0xBB, 0xBE and 0xC1 inaccessible (0xB0)
+    RestrictedInaccessible,
+    /// Make 0xA2, 0xB1, 0xB3, 0xBB, 0xBE and 0xC1 accessible (0xB1)
+    RestrictedAccessible,
+}
+
+/// Parallel interface width selected by function selection (0xAB).
+///
+/// The setting is ignored when the controller is wired for the serial interface.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParallelInterface {
+    /// 8-bit parallel interface
+    Bits8,
+    /// 16-bit parallel interface
+    Bits16,
+    /// 18-bit parallel interface
+    Bits18,
+}
+
+/// Colour depth of the GDDRAM, set by the re-map register (0xA0).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColourDepth {
+    /// 65k colours, two bytes per pixel
+    Colours65k,
+    /// 262k colours, three bytes per pixel
+    Colours262k,
+    /// 262k colours, in the 16-bit format 2 of the 16-bit parallel interface
+    Colours262kFormat2,
+}
+
+/// Re-map and colour depth setting (0xA0).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Remap {
+    /// Increment the row address after each pixel, the column address otherwise
+    pub vertical_increment: bool,
+    /// Map the column address 0 to SEG127 instead of SEG0
+    pub column_remap: bool,
+    /// Send the colour channels in the sequence C, B, A, the red bits of the pixels go to colour A
+    pub colour_swap: bool,
+    /// Scan from COM[N-1] to COM0
+    pub com_scan_reverse: bool,
+    /// Split the odd and even COM lines
+    pub com_split: bool,
+    /// Colour depth of the GDDRAM
+    pub colour_depth: ColourDepth,
+}
+
+impl Default for Remap {
+    /// Returns the setting of the `Config` presets (0x74).
+    fn default() -> Self {
+        Self {
+            vertical_increment: false,
+            column_remap: false,
+            colour_swap: true,
+            com_scan_reverse: true,
+            com_split: true,
+            colour_depth: ColourDepth::Colours65k,
+        }
+    }
+}
+
+impl Remap {
+    /// Returns the value of the register.
+    fn bits(self) -> u8 {
+        let depth = match self.colour_depth {
+            ColourDepth::Colours65k => 0x40,
+            ColourDepth::Colours262k => 0x80,
+            ColourDepth::Colours262kFormat2 => 0xC0,
+        };
+        depth
+            | (self.com_split as u8) << 5
+            | (self.com_scan_reverse as u8) << 4
+            | (self.colour_swap as u8) << 2
+            | (self.column_remap as u8) << 1
+            | self.vertical_increment as u8
+    }
+}
+
+/// Setting of a GPIO pin (0xB5).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GpioMode {
+    /// High impedance, input disabled
+    InputDisabled,
+    /// High impedance, input enabled
+    InputEnabled,
+    /// Output low
+    OutputLow,
+    /// Output high
+    OutputHigh,
+}
+
+/// Time interval between each horizontal scroll step (0x96).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScrollSpeed {
+    /// Test mode
+    Test,
+    /// Normal
+    Normal,
+    /// Slow
+    Slow,
+    /// Slowest
+    Slowest,
+}
 
 /// Holds commands which can be sent to the display.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Command {
-    Unlock0x12,
-    Unlock0xB1,
+    /// Set up column start and end address (0x15)
+    ColumnAddress {
+        /// The start column address (0 - 127)
+        start: u8,
+        /// The end column address (start - 127)
+        end: u8,
+    },
+    /// Set up row start and end address (0x75)
+    RowAddress {
+        /// The start row address (0 - 127)
+        start: u8,
+        /// The end row address (start - 127)
+        end: u8,
+    },
+    /// Enable writing the following data into the GDDRAM (0x5C)
+    WriteRam,
+    /// Re-map and colour depth setting (0xA0)
+    SetRemap(Remap),
+    /// Display start line (0xA1), 0 - 127
+    DisplayStartLine(u8),
+    /// Vertical scroll by setting the display offset (0xA2), 0 - 127
+    DisplayOffset(u8),
+    /// Display mode (0xA4 - 0xA7)
+    DisplayMode(DisplayMode),
+    /// Function selection (0xAB)
+    FunctionSelection {
+        /// Enable the internal VDD regulator
+        internal_regulator: bool,
+        /// Parallel interface width
+        interface: ParallelInterface,
+    },
+    /// Sleep mode on, the display is turned off (0xAE)
     DisplayOff,
-    ClockDivider,
-    Muxratio,
-    ColumnAddress,
-    RowAddress,
-    SegmentRemapping,
-    DisplayStartLine,
-    DisplayOffset,
-    SetGpio,
-    SelectInternalDiodeDrop,
-    Precharge,
-    SetSegmentLowVoltage,
-    SetVcomHVoltage,
-    Invert,
-    ContrastMaster,
-    Precharge2,
-    Contrast,
+    /// Sleep mode off, the display is turned on (0xAF)
     DisplayOn,
-    NomalDisplay,
-    WriteRam, // /// Turn display off (0xAE)
-              // DisplayOff,
-              // /// Turn display on (0xAF)
-              // DisplayOn,
-              // /// Set up column start and end address (0x15)
-              // ColumnAddress {
-              //     /// The start column address
-              //     start: u8,
-              //     /// The end column address
-              //     end: u8,
-              // },
-              // /// Set up row start and end address (0x75)
-              // RowAddress {
-              //     /// The start row address
-              //     start: u8,
-              //     /// The end row address
-              //     end: u8,
-              // },
-              // /// Contrast Control (0x81)
-              // Contrast(u8),
-              // /// Re-map setting in Graphic Display Data RAM  (0xA0)
-              // SetRemap(u8),
-              // /// Display Start Line (0xA1)
-              // StartLine(u8),
-              // /// Display Offset (0xA2)
-              // Offset(u8),
-              // /// Normal Display Mode (0xA4)
-              // DisplayModeNormal,
-              // /// Multiplex Ratio (0xA8)
-              // MuxRatio(u8),
-              // /// Phase Length (0xB1)
-              // PhaseLength(u8),
-              // /// Front Clock Divider / Oscillator Frequency (0xB3)
-              // FrontClockDivider(u8),
-              // /// Function Selection A (0xAB)
-              // FunctionSelectionA(u8),
-              // /// Second Pre-Charge Period (0xB6)
-              // SecondPreChargePeriod(u8),
-              // /// COM deselect voltage level (0xBE)
-              // ComVoltageLevel(u8),
-              // /// Pre-Charge Voltage (0xBC)
-              // PreChargeVoltage(u8),
-              // /// Function Selection B (0xD5)
-              // FunctionSelectionB(u8),
-              // /// Function Selection B (0xD5)
-              // CommandLock(u8)
+    /// Reset (phase 1) and pre-charge (phase 2) periods (0xB1)
+    PhaseLength {
+        /// Phase 1 period in DCLK clocks (5 - 31, odd values only)
+        phase1: u8,
+        /// Phase 2 period in DCLK clocks (3 - 15)
+        phase2: u8,
+    },
+    /// Front clock divider and oscillator frequency (0xB3)
+    ClockDivider {
+        /// Divide the DCLK by 2^divider (0 - 10)
+        divider: u8,
+        /// Oscillator frequency (0 - 15), the frequency increases with the value
+        frequency: u8,
+    },
+    /// Segment low voltage (0xB4)
+    SegmentLowVoltage(SegmentLowVoltage),
+    /// GPIO0 and GPIO1 settings (0xB5)
+    SetGpio {
+        /// Setting of GPIO0
+        gpio0: GpioMode,
+        /// Setting of GPIO1
+        gpio1: GpioMode,
+    },
+    /// Second pre-charge period in DCLK clocks (0xB6), 1 - 15
+    SecondPrechargePeriod(u8),
+    /// Gray scale pulse widths, in DCLK clocks, for GS1 - GS63 (0xB8), 0 - 180 each
+    GrayscaleTable([u8; GRAYSCALE_TABLE_LEN]),
+    /// Restore the built-in linear gray scale table (0xB9)
+    DefaultGrayscaleTable,
+    /// Pre-charge voltage level (0xBB), 0 - 31
+    PrechargeVoltage(u8),
+    /// COM deselect voltage level (0xBE), 0 - 7
+    VcomhVoltage(u8),
+    /// Contrast current for colours A, B and C (0xC1)
+    Contrast {
+        /// Contrast of colour A
+        a: u8,
+        /// Contrast of colour B
+        b: u8,
+        /// Contrast of colour C
+        c: u8,
+    },
+    /// Master contrast current (0xC7), 0 - 15
+    MasterContrast(u8),
+    /// Multiplex ratio (0xCA), 15 - 127
+    MuxRatio(u8),
+    /// Command lock (0xFD)
+    CommandLock(CommandLock),
+    /// Horizontal scroll setup (0x96)
+    HorizontalScroll {
+        /// Scroll step: 0 for no scrolling, 1 - 63 towards SEG127, 64 - 255 towards SEG0
+        shift: u8,
+        /// Start row address (0 - 127)
+        start_row: u8,
+        /// Number of rows to be scrolled (start_row + rows <= 128)
+        rows: u8,
+        /// Time interval between each scroll step
+        speed: ScrollSpeed,
+    },
+    /// Stop horizontal scroll (0x9E)
+    StopScroll,
+    /// Start horizontal scroll (0x9F)
+    StartScroll,
 }
 
 impl Command {
-    pub(crate) fn send<DI>(self, display: &mut DI) -> Result<(), DisplayError>
+    pub(crate) fn send<DI>(self, display: &mut DI) -> Result<(), Error>
     where
         DI: WriteOnlyDataCommand,
     {
-        let (command, data, len) = match self {
-            Self::Unlock0x12 => (0xFD, [0x12, 0, 0], 1),
-            Self::Unlock0xB1 => (0xFD, [0xB1, 0, 0], 1),
-            Self::DisplayOff => (0xAE, [0, 0, 0], 0),
-            Self::ClockDivider => (0xB3, [0xF1, 0, 0], 1),
-            Self::Muxratio => (0xCA, [0x7F, 0, 0], 1),
-            Self::ColumnAddress => (0x15, [0x00, 0x7F, 0], 2),
-            Self::RowAddress => (0x75, [0x00, 0x7F, 0], 2),
-            Self::SegmentRemapping => (0xA0, [0x74, 0, 0], 1),
-            Self::DisplayStartLine => (0xA1, [0x00, 0, 0], 1),
-            Self::DisplayOffset => (0xA2, [0x00, 0, 0], 1),
-            Self::SetGpio => (0xB5, [0x00, 0, 0], 1),
-            Self::SelectInternalDiodeDrop => (0xAB, [0x01, 0, 0], 1),
-            Self::Precharge => (0xB1, [0x32, 0, 0], 1),
-            Self::SetSegmentLowVoltage => (0xB4, [0xA0, 0xB5, 0x55], 3),
-            Self::SetVcomHVoltage => (0xBE, [0x05, 0, 0], 1),
-            Self::Invert => (0xA7, [0x00, 0, 0], 0),
-            Self::ContrastMaster => (0xC7, [0x0F, 0, 0], 1),
-            Self::Precharge2 => (0xB6, [0x01, 0, 0], 1),
-            Self::Contrast => (0xC1, [0xFF, 0xFF, 0xFF], 3),
-            Self::DisplayOn => (0xAF, [0, 0, 0], 0),
-            Self::NomalDisplay => (0xA6, [0, 0, 0], 0),
-            Self::WriteRam => (0x5C, [0, 0, 0], 0),
-            // Self::DisplayOn => ([0xAF, 0, 0], 1),
-            // Self::DisplayOff => ([0xAE, 0, 0], 1),
-            // Self::ColumnAddress { start, end } => ([0x15, start, end], 3),
-            // Self::RowAddress { start, end } => ([0x75, start, end], 3),
-            // Self::Contrast(value) => ([0x81, value, 0], 2),
-            // Self::SetRemap(value) => ([0xA0, value, 0], 2),
-            // Self::StartLine(value) => ([0xA1, value, 0], 2),
-            // Self::Offset(value) => ([0xA2, value, 0], 2),
-            // Self::DisplayModeNormal => ([0xA4, 0, 0], 1),
-            // Self::MuxRatio(value) => ([0xA8, value, 0], 2),
-            // Self::PhaseLength(value) => ([0xB1, value, 0], 2),
-            // Self::FrontClockDivider(value) => ([0xB3, value, 0], 2),
-            // Self::FunctionSelectionA(value) => ([0xAB, value, 0], 2),
-            // Self::SecondPreChargePeriod(value) => ([0xB6, value, 0], 2),
-            // Self::ComVoltageLevel(value) => ([0xBE, value, 0], 2),
-            // Self::PreChargeVoltage(value) => ([0xBC, value, 0], 2),
-            // Self::FunctionSelectionB(value) => ([0xD5, value, 0], 2),
-            // Self::CommandLock(value) => ([0xFD, value, 0], 2),
-        };
-        //display.send_commands(U8(&data[0..len]))
+        self.validate()?;
+
+        let mut params = [0; 5];
+        let (command, data) = self.encode(&mut params);
+
         // Send command over the interface
         display.send_commands(U8(&[command]))?;
 
-        if len > 0 {
-            display.send_data(U8(&data[0..len]))
+        if !data.is_empty() {
+            Ok(display.send_data(U8(data))?)
+        } else {
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "async")]
+    pub(crate) async fn send_async<DI>(self, display: &mut DI) -> Result<(), Error>
+    where
+        DI: AsyncWriteOnlyDataCommand,
+    {
+        self.validate()?;
+
+        let mut params = [0; 5];
+        let (command, data) = self.encode(&mut params);
+
+        display.send_commands(U8(&[command])).await?;
+
+        if !data.is_empty() {
+            Ok(display.send_data(U8(data)).await?)
         } else {
             Ok(())
         }
     }
+
+    /// Returns the command byte and its arguments.
+    ///
+    /// Short arguments are written to `params`, the gray scale table is borrowed from the command.
+    pub(crate) fn encode<'a>(&'a self, params: &'a mut [u8; 5]) -> (u8, &'a [u8]) {
+        match *self {
+            Self::ColumnAddress { start, end } => {
+                *params = [start, end, 0, 0, 0];
+                (0x15, &params[..2])
+            }
+            Self::RowAddress { start, end } => {
+                *params = [start, end, 0, 0, 0];
+                (0x75, &params[..2])
+            }
+            Self::WriteRam => (0x5C, &[]),
+            Self::SetRemap(remap) => {
+                *params = [remap.bits(), 0, 0, 0, 0];
+                (0xA0, &params[..1])
+            }
+            Self::DisplayStartLine(line) => {
+                *params = [line, 0, 0, 0, 0];
+                (0xA1, &params[..1])
+            }
+            Self::DisplayOffset(offset) => {
+                *params = [offset, 0, 0, 0, 0];
+                (0xA2, &params[..1])
+            }
+            Self::DisplayMode(mode) => match mode {
+                DisplayMode::AllOff => (0xA4, &[]),
+                DisplayMode::AllOn => (0xA5, &[]),
+                DisplayMode::Normal => (0xA6, &[]),
+                DisplayMode::Inverse => (0xA7, &[]),
+            },
+            Self::FunctionSelection {
+                internal_regulator,
+                interface,
+            } => {
+                let interface = match interface {
+                    ParallelInterface::Bits8 => 0x00,
+                    ParallelInterface::Bits16 => 0x40,
+                    ParallelInterface::Bits18 => 0xC0,
+                };
+                *params = [interface | internal_regulator as u8, 0, 0, 0, 0];
+                (0xAB, &params[..1])
+            }
+            Self::DisplayOff => (0xAE, &[]),
+            Self::DisplayOn => (0xAF, &[]),
+            Self::PhaseLength { phase1, phase2 } => {
+                *params = [phase2 << 4 | phase1 >> 1, 0, 0, 0, 0];
+                (0xB1, &params[..1])
+            }
+            Self::ClockDivider { divider, frequency } => {
+                *params = [frequency << 4 | divider, 0, 0, 0, 0];
+                (0xB3, &params[..1])
+            }
+            Self::SegmentLowVoltage(vsl) => {
+                let value = match vsl {
+                    SegmentLowVoltage::External => 0xA0,
+                    SegmentLowVoltage::Internal => 0xA2,
+                };
+                *params = [value, 0xB5, 0x55, 0, 0];
+                (0xB4, &params[..3])
+            }
+            Self::SetGpio { gpio0, gpio1 } => {
+                let mode = |gpio| match gpio {
+                    GpioMode::InputDisabled => 0x00,
+                    GpioMode::InputEnabled => 0x01,
+                    GpioMode::OutputLow => 0x02,
+                    GpioMode::OutputHigh => 0x03,
+                };
+                *params = [mode(gpio1) << 2 | mode(gpio0), 0, 0, 0, 0];
+                (0xB5, &params[..1])
+            }
+            Self::SecondPrechargePeriod(period) => {
+                *params = [period, 0, 0, 0, 0];
+                (0xB6, &params[..1])
+            }
+            Self::GrayscaleTable(ref table) => (0xB8, &table[..]),
+            Self::DefaultGrayscaleTable => (0xB9, &[]),
+            Self::PrechargeVoltage(level) => {
+                *params = [level, 0, 0, 0, 0];
+                (0xBB, &params[..1])
+            }
+            Self::VcomhVoltage(level) => {
+                *params = [level, 0, 0, 0, 0];
+                (0xBE, &params[..1])
+            }
+            Self::Contrast { a, b, c } => {
+                *params = [a, b, c, 0, 0];
+                (0xC1, &params[..3])
+            }
+            Self::MasterContrast(level) => {
+                *params = [level, 0, 0, 0, 0];
+                (0xC7, &params[..1])
+            }
+            Self::MuxRatio(ratio) => {
+                *params = [ratio, 0, 0, 0, 0];
+                (0xCA, &params[..1])
+            }
+            Self::CommandLock(lock) => {
+                let value = match lock {
+                    CommandLock::Unlock => 0x12,
+                    CommandLock::Lock => 0x16,
+                    CommandLock::RestrictedInaccessible => 0xB0,
+                    CommandLock::RestrictedAccessible => 0xB1,
+                };
+                *params = [value, 0, 0, 0, 0];
+                (0xFD, &params[..1])
+            }
+            Self::HorizontalScroll {
+                shift,
+                start_row,
+                rows,
+                speed,
+            } => {
+                let speed = match speed {
+                    ScrollSpeed::Test => 0x00,
+                    ScrollSpeed::Normal => 0x01,
+                    ScrollSpeed::Slow => 0x02,
+                    ScrollSpeed::Slowest => 0x03,
+                };
+                *params = [shift, start_row, rows, 0x00, speed];
+                (0x96, &params[..5])
+            }
+            Self::StopScroll => (0x9E, &[]),
+            Self::StartScroll => (0x9F, &[]),
+        }
+    }
+
+    /// Checks that the arguments are within the ranges accepted by the controller.
+    pub(crate) fn validate(&self) -> Result<(), Error> {
+        let valid = match *self {
+            Self::ColumnAddress { start, end } | Self::RowAddress { start, end } => {
+                start <= end && end <= 127
+            }
+            Self::DisplayStartLine(value) | Self::DisplayOffset(value) => value <= 127,
+            Self::PhaseLength { phase1, phase2 } => {
+                (5..=31).contains(&phase1) && phase1 % 2 == 1 && (3..=15).contains(&phase2)
+            }
+            Self::ClockDivider { divider, frequency } => divider <= 10 && frequency <= 15,
+            Self::SecondPrechargePeriod(period) => (1..=15).contains(&period),
+            Self::GrayscaleTable(ref table) => table.iter().all(|&width| width <= GRAYSCALE_MAX),
+            Self::PrechargeVoltage(level) => level <= 31,
+            Self::VcomhVoltage(level) => level <= 7,
+            Self::MasterContrast(level) => level <= 15,
+            Self::MuxRatio(ratio) => (15..=127).contains(&ratio),
+            Self::HorizontalScroll {
+                start_row, rows, ..
+            } => start_row <= 127 && u16::from(start_row) + u16::from(rows) <= 128,
+            _ => true,
+        };
+
+        if valid {
+            Ok(())
+        } else {
+            Err(Error::InvalidArgument)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encoded(command: Command) -> (u8, Vec<u8>) {
+        let mut params = [0; 5];
+        let (command, data) = command.encode(&mut params);
+        (command, data.to_vec())
+    }
+
+    #[test]
+    fn phase_length_encodes_half_periods() {
+        let phases = |phase1, phase2| Command::PhaseLength { phase1, phase2 };
+        assert_eq!(encoded(phases(5, 3)), (0xB1, vec![0x32]));
+        assert_eq!(encoded(phases(31, 15)), (0xB1, vec![0xFF]));
+        assert!(phases(5, 3).validate().is_ok());
+        assert!(phases(31, 15).validate().is_ok());
+        assert!(phases(6, 3).validate().is_err());
+        assert!(phases(3, 3).validate().is_err());
+        assert!(phases(33, 3).validate().is_err());
+        assert!(phases(5, 2).validate().is_err());
+        assert!(phases(5, 16).validate().is_err());
+    }
+
+    #[test]
+    fn remap_encodes_its_bits() {
+        assert_eq!(
+            encoded(Command::SetRemap(Remap::default())),
+            (0xA0, vec![0x74])
+        );
+        let remap = Remap {
+            vertical_increment: true,
+            column_remap: true,
+            colour_swap: false,
+            com_scan_reverse: false,
+            com_split: false,
+            colour_depth: ColourDepth::Colours262k,
+        };
+        assert_eq!(encoded(Command::SetRemap(remap)), (0xA0, vec![0x83]));
+        let remap = Remap {
+            colour_depth: ColourDepth::Colours262kFormat2,
+            ..Remap::default()
+        };
+        assert_eq!(encoded(Command::SetRemap(remap)), (0xA0, vec![0xF4]));
+    }
+
+    #[test]
+    fn gpio_encodes_both_pins() {
+        let gpio = Command::SetGpio {
+            gpio0: GpioMode::OutputHigh,
+            gpio1: GpioMode::InputEnabled,
+        };
+        assert_eq!(encoded(gpio), (0xB5, vec![0x07]));
+    }
+
+    #[test]
+    fn clock_divider_is_range_checked() {
+        let clock = |divider, frequency| Command::ClockDivider { divider, frequency };
+        assert_eq!(encoded(clock(1, 15)), (0xB3, vec![0xF1]));
+        assert!(clock(10, 15).validate().is_ok());
+        assert!(clock(11, 15).validate().is_err());
+        assert!(clock(0, 16).validate().is_err());
+    }
+
+    #[test]
+    fn addresses_are_range_checked() {
+        let columns = |start, end| Command::ColumnAddress { start, end };
+        assert_eq!(encoded(columns(0, 127)), (0x15, vec![0, 127]));
+        assert!(columns(127, 127).validate().is_ok());
+        assert!(columns(8, 7).validate().is_err());
+        assert!(columns(0, 128).validate().is_err());
+    }
 }
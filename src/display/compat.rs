@@ -0,0 +1,95 @@
+//! Adapters for the embedded-hal 0.2 traits
+//!
+//! The drivers are built on embedded-hal 1.0 and display-interface 0.5. These adapters let HALs
+//! which only implement embedded-hal 0.2 drive the display:
+//!
+//! ```ignore
+//! let spii = SpiInterfaceNoCs::new(spi, dc);
+//! let mut disp = Ssd1351::new(spii);
+//! disp.reset(&mut Hal02(rst), &mut Hal02(delay))?;
+//! ```
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+use embedded_hal::{delay::DelayNs, digital};
+use embedded_hal_02::{
+    blocking::{delay::DelayUs, spi::Write},
+    digital::v2::OutputPin,
+};
+
+/// Wraps an embedded-hal 0.2 output pin or delay to implement the embedded-hal 1.0 traits.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Hal02<T>(pub T);
+
+impl<T: OutputPin> digital::ErrorType for Hal02<T> {
+    type Error = digital::ErrorKind;
+}
+
+impl<T: OutputPin> digital::OutputPin for Hal02<T> {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.0.set_low().map_err(|_| digital::ErrorKind::Other)
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.0.set_high().map_err(|_| digital::ErrorKind::Other)
+    }
+}
+
+impl<T: DelayUs<u32>> DelayNs for Hal02<T> {
+    fn delay_ns(&mut self, ns: u32) {
+        self.0.delay_us(ns.div_ceil(1000));
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        self.0.delay_us(us);
+    }
+}
+
+/// SPI display interface over an embedded-hal 0.2 bus, without chip select management.
+///
+/// The data/command pin is low while commands are written and high while data is written.
+#[derive(Debug)]
+pub struct SpiInterfaceNoCs<SPI, DC> {
+    spi: SPI,
+    dc: DC,
+}
+
+impl<SPI, DC> SpiInterfaceNoCs<SPI, DC>
+where
+    SPI: Write<u8>,
+    DC: OutputPin,
+{
+    /// Creates the interface from the SPI bus and the data/command pin.
+    pub fn new(spi: SPI, dc: DC) -> Self {
+        Self { spi, dc }
+    }
+
+    /// Returns the SPI bus and the data/command pin.
+    pub fn release(self) -> (SPI, DC) {
+        (self.spi, self.dc)
+    }
+
+    fn write(&mut self, bytes: DataFormat<'_>) -> Result<(), DisplayError> {
+        match bytes {
+            DataFormat::U8(bytes) => self
+                .spi
+                .write(bytes)
+                .map_err(|_| DisplayError::BusWriteError),
+            _ => Err(DisplayError::DataFormatNotImplemented),
+        }
+    }
+}
+
+impl<SPI, DC> WriteOnlyDataCommand for SpiInterfaceNoCs<SPI, DC>
+where
+    SPI: Write<u8>,
+    DC: OutputPin,
+{
+    fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.dc.set_low().map_err(|_| DisplayError::DCError)?;
+        self.write(cmd)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.dc.set_high().map_err(|_| DisplayError::DCError)?;
+        self.write(buf)
+    }
+}
@@ -0,0 +1,373 @@
+//! Settings applied to the display when it is turned on
+
+use crate::display::command::{
+    Command, CommandLock, DisplayMode, GpioMode, ParallelInterface, Remap, SegmentLowVoltage,
+};
+
+/// Number of commands sent by `turn_on`.
+const INIT_SEQUENCE_LEN: usize = 21;
+
+/// Order of the colour channels wired to the panel.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColourOrder {
+    /// Red, green and blue, the order of the presets
+    #[default]
+    Rgb,
+    /// Blue, green and red
+    Bgr,
+}
+
+/// Order of the bytes of the 16-bit colours sent to the controller.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ByteOrder {
+    /// High byte first, as the controller reads them
+    #[default]
+    BigEndian,
+    /// Low byte first, for buses swapping the bytes of the words
+    LittleEndian,
+}
+
+impl ByteOrder {
+    /// Returns the bytes of the 16-bit colour `raw` in this order.
+    pub(crate) fn bytes(self, raw: u16) -> [u8; 2] {
+        match self {
+            Self::BigEndian => raw.to_be_bytes(),
+            Self::LittleEndian => raw.to_le_bytes(),
+        }
+    }
+}
+
+/// Holds the panel geometry and the settings sent to the display by `turn_on`.
+///
+/// The default values match the 128x128 modules the driver was written for. Start from one of the
+/// presets, or use the builder methods to adjust them to other panels:
+///
+/// ```
+/// use ssd1351::display::config::Config;
+///
+/// let config = Config::adafruit_128x96().master_contrast(10);
+/// ```
+///
+/// The arguments are checked against the ranges accepted by the controller when the commands are
+/// sent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Config {
+    pub(crate) width: u8,
+    pub(crate) height: u8,
+    pub(crate) column_offset: u8,
+    pub(crate) row_offset: u8,
+    clock_divider: u8,
+    oscillator_frequency: u8,
+    mux_ratio: u8,
+    pub(crate) remap: Remap,
+    pub(crate) start_line: u8,
+    display_offset: u8,
+    pub(crate) parallel_interface: ParallelInterface,
+    pub(crate) byte_order: ByteOrder,
+    phase1: u8,
+    phase2: u8,
+    second_precharge_period: u8,
+    segment_low_voltage: SegmentLowVoltage,
+    vcomh_voltage: u8,
+    precharge_voltage: Option<u8>,
+    contrast: [u8; 3],
+    master_contrast: u8,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            width: 128,
+            height: 128,
+            column_offset: 0,
+            row_offset: 0,
+            clock_divider: 1,
+            oscillator_frequency: 15,
+            mux_ratio: 127,
+            remap: Remap::default(),
+            start_line: 0,
+            display_offset: 0,
+            parallel_interface: ParallelInterface::Bits8,
+            byte_order: ByteOrder::BigEndian,
+            phase1: 5,
+            phase2: 3,
+            second_precharge_period: 1,
+            segment_low_voltage: SegmentLowVoltage::External,
+            vcomh_voltage: 5,
+            precharge_voltage: None,
+            contrast: [0xFF, 0xFF, 0xFF],
+            master_contrast: 15,
+        }
+    }
+}
+
+impl Config {
+    /// Creates the default configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adafruit 1.5" 128x128 colour OLED breakout.
+    pub fn adafruit_128x128() -> Self {
+        Self::default().contrast(0xC8, 0x80, 0xC8)
+    }
+
+    /// Adafruit 1.27" 128x96 colour OLED breakout.
+    ///
+    /// The panel shows 96 of the 128 GDDRAM rows, the start line moves them to the visible area.
+    /// Use it with a `Ssd1351<_, 128, 96>` display.
+    pub fn adafruit_128x96() -> Self {
+        Self::default()
+            .geometry(128, 96)
+            .start_line(96)
+            .contrast(0xC8, 0x80, 0xC8)
+    }
+
+    /// Waveshare 1.5" 128x128 RGB OLED module.
+    pub fn waveshare_128x128() -> Self {
+        Self::default()
+            .contrast(0xC8, 0x80, 0xC0)
+            .precharge_voltage(0x17)
+    }
+
+    /// Sets the number of columns (1 - 128) and rows (1 - 128) of the panel.
+    ///
+    /// The drivers of the `display` module check that it matches the geometry of their type.
+    pub fn geometry(mut self, width: u8, height: u8) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    /// Sets the first GDDRAM column and row wired to the panel.
+    pub fn ram_offset(mut self, column: u8, row: u8) -> Self {
+        self.column_offset = column;
+        self.row_offset = row;
+        self
+    }
+
+    /// Sets the front clock divider (0 - 10) and the oscillator frequency (0 - 15).
+    pub fn clock_divider(mut self, divider: u8, frequency: u8) -> Self {
+        self.clock_divider = divider;
+        self.oscillator_frequency = frequency;
+        self
+    }
+
+    /// Sets the multiplex ratio (15 - 127), the number of rows minus one.
+    pub fn mux_ratio(mut self, ratio: u8) -> Self {
+        self.mux_ratio = ratio;
+        self
+    }
+
+    /// Sets the re-map and colour depth register (0xA0).
+    ///
+    /// The drivers replace the colour depth with the one of their colour type, and the address
+    /// increment, column remap and COM scan direction with the ones of the rotation.
+    pub fn remap(mut self, remap: Remap) -> Self {
+        self.remap = remap;
+        self
+    }
+
+    /// Sets the order of the colour channels, with the colour sequence of the re-map register.
+    ///
+    /// Panels wired BGR show red and blue swapped with the presets. Set it after `remap`, which
+    /// replaces the colour sequence.
+    pub fn colour_order(mut self, order: ColourOrder) -> Self {
+        self.remap.colour_swap = match order {
+            ColourOrder::Rgb => true,
+            ColourOrder::Bgr => false,
+        };
+        self
+    }
+
+    /// Sets the order of the bytes of the `Rgb565` colours sent to the controller.
+    ///
+    /// The 262k colour mode sends one byte per channel and ignores it.
+    pub fn byte_order(mut self, order: ByteOrder) -> Self {
+        self.byte_order = order;
+        self
+    }
+
+    /// Sets the display start line (0 - 127).
+    pub fn start_line(mut self, line: u8) -> Self {
+        self.start_line = line;
+        self
+    }
+
+    /// Sets the display offset (0 - 127).
+    pub fn display_offset(mut self, offset: u8) -> Self {
+        self.display_offset = offset;
+        self
+    }
+
+    /// Sets the width of the parallel bus wired to the controller.
+    ///
+    /// The 8080 and 6800 buses are selected by the BS pins of the module, the width is sent by
+    /// `turn_on`. The pixels are sent as bytes on the 8-bit bus and as 16-bit words on the 16-bit
+    /// bus, which carries `Rgb565` pixels only. The 18-bit bus is not supported by the drivers. The
+    /// `turn_on` of the drivers fails with `Error::InvalidArgument` when the bus cannot carry their
+    /// pixels. The setting is ignored by the serial interfaces.
+    pub fn parallel_interface(mut self, interface: ParallelInterface) -> Self {
+        self.parallel_interface = interface;
+        self
+    }
+
+    /// Sets the reset (5 - 31, odd values) and pre-charge (3 - 15) phase lengths in DCLK clocks.
+    pub fn phase_length(mut self, phase1: u8, phase2: u8) -> Self {
+        self.phase1 = phase1;
+        self.phase2 = phase2;
+        self
+    }
+
+    /// Sets the second pre-charge period (1 - 15) in DCLK clocks.
+    pub fn second_precharge_period(mut self, period: u8) -> Self {
+        self.second_precharge_period = period;
+        self
+    }
+
+    /// Sets the segment low voltage source.
+    pub fn segment_low_voltage(mut self, vsl: SegmentLowVoltage) -> Self {
+        self.segment_low_voltage = vsl;
+        self
+    }
+
+    /// Sets the COM deselect voltage level (0 - 7).
+    pub fn vcomh_voltage(mut self, level: u8) -> Self {
+        self.vcomh_voltage = level;
+        self
+    }
+
+    /// Sets the pre-charge voltage level (0 - 31).
+    ///
+    /// The controller keeps its reset value when it is not set.
+    pub fn precharge_voltage(mut self, level: u8) -> Self {
+        self.precharge_voltage = Some(level);
+        self
+    }
+
+    /// Sets the contrast current of colours A, B and C.
+    pub fn contrast(mut self, a: u8, b: u8, c: u8) -> Self {
+        self.contrast = [a, b, c];
+        self
+    }
+
+    /// Sets the contrast current of the red, green and blue channels.
+    ///
+    /// The channels are matched to the colours A, B and C with the colour order, set it first.
+    pub fn channel_contrast(mut self, red: u8, green: u8, blue: u8) -> Self {
+        // The swapped colour sequence sends the red bits of the pixels to colour A
+        self.contrast = if self.remap.colour_swap {
+            [red, green, blue]
+        } else {
+            [blue, green, red]
+        };
+        self
+    }
+
+    /// Sets the master contrast current (0 - 15).
+    pub fn master_contrast(mut self, level: u8) -> Self {
+        self.master_contrast = level;
+        self
+    }
+
+    /// Returns the command selecting the columns of the panel.
+    pub(crate) fn column_address(&self) -> Command {
+        Command::ColumnAddress {
+            start: self.column_offset,
+            end: self
+                .column_offset
+                .saturating_add(self.width)
+                .wrapping_sub(1),
+        }
+    }
+
+    /// Returns the command selecting the rows of the panel.
+    pub(crate) fn row_address(&self) -> Command {
+        Command::RowAddress {
+            start: self.row_offset,
+            end: self.row_offset.saturating_add(self.height).wrapping_sub(1),
+        }
+    }
+
+    /// Returns the command setting the contrast current of the colours.
+    pub(crate) fn contrast_command(&self) -> Command {
+        Command::Contrast {
+            a: self.contrast[0],
+            b: self.contrast[1],
+            c: self.contrast[2],
+        }
+    }
+
+    /// Returns the number of pixels of the panel.
+    #[cfg(feature = "std")]
+    pub(crate) fn pixel_count(&self) -> usize {
+        usize::from(self.width) * usize::from(self.height)
+    }
+
+    /// Returns the commands which initialize the display.
+    pub(crate) fn init_sequence(&self) -> impl Iterator<Item = Command> {
+        let sequence: [Option<Command>; INIT_SEQUENCE_LEN] = [
+            Some(Command::CommandLock(CommandLock::Unlock)),
+            Some(Command::CommandLock(CommandLock::RestrictedAccessible)),
+            Some(Command::DisplayOff),
+            Some(Command::ClockDivider {
+                divider: self.clock_divider,
+                frequency: self.oscillator_frequency,
+            }),
+            Some(Command::MuxRatio(self.mux_ratio)),
+            Some(self.column_address()),
+            Some(self.row_address()),
+            Some(Command::SetRemap(self.remap)),
+            Some(Command::DisplayStartLine(self.start_line)),
+            Some(Command::DisplayOffset(self.display_offset)),
+            Some(Command::SetGpio {
+                gpio0: GpioMode::InputDisabled,
+                gpio1: GpioMode::InputDisabled,
+            }),
+            Some(Command::FunctionSelection {
+                internal_regulator: true,
+                interface: self.parallel_interface,
+            }),
+            Some(Command::PhaseLength {
+                phase1: self.phase1,
+                phase2: self.phase2,
+            }),
+            Some(Command::SegmentLowVoltage(self.segment_low_voltage)),
+            self.precharge_voltage.map(Command::PrechargeVoltage),
+            Some(Command::VcomhVoltage(self.vcomh_voltage)),
+            Some(Command::MasterContrast(self.master_contrast)),
+            Some(Command::SecondPrechargePeriod(self.second_precharge_period)),
+            Some(self.contrast_command()),
+            Some(Command::DisplayOn),
+            Some(Command::DisplayMode(DisplayMode::Normal)),
+        ];
+        IntoIterator::into_iter(sequence).flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Returns the parameters of the contrast command
+    fn contrast_bytes(config: Config) -> Vec<u8> {
+        let mut params = [0; 5];
+        let contrast = config.contrast_command();
+        let (command, data) = contrast.encode(&mut params);
+        assert_eq!(command, 0xC1);
+        data.to_vec()
+    }
+
+    #[test]
+    fn channel_contrast_follows_colour_order() {
+        let rgb = Config::default()
+            .colour_order(ColourOrder::Rgb)
+            .channel_contrast(1, 2, 3);
+        assert_eq!(contrast_bytes(rgb), [1, 2, 3]);
+
+        let bgr = Config::default()
+            .colour_order(ColourOrder::Bgr)
+            .channel_contrast(1, 2, 3);
+        assert_eq!(contrast_bytes(bgr), [3, 2, 1]);
+    }
+}
@@ -0,0 +1,519 @@
+//! Settings and lifecycle of the controller shared by the display drivers
+//!
+//! The `Controller` returns the commands and GDDRAM windows to send, the drivers only send them
+//! over their blocking or async interface. The lifecycle state changes once the drivers sent every
+//! command, a failed transfer leaves it unchanged.
+use crate::display::{
+    colour::{self, ColourMode},
+    command::{Command, ParallelInterface, ScrollSpeed},
+    config::Config,
+    dirty::DirtyArea,
+    display::{GDDRAM_HEIGHT, GDDRAM_WIDTH},
+    frame::Frame,
+    rotation::{Orientation, Rotation},
+    shadow::Shadow,
+    state::State,
+};
+use crate::Error;
+use core::{array, iter::Flatten, mem::size_of, ops::Range};
+use embedded_graphics::{
+    geometry::{Point, Size},
+    primitives::Rectangle,
+};
+
+/// Commands to send to the controller, in order.
+pub(crate) type Commands<const N: usize> = Flatten<array::IntoIter<Option<Command>, N>>;
+
+/// Returns the commands which are set.
+fn commands<const N: usize>(commands: [Option<Command>; N]) -> Commands<N> {
+    IntoIterator::into_iter(commands).flatten()
+}
+
+/// Words of the bus carrying the pixels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum PixelFormat {
+    /// The bytes of the pixels, on the serial interfaces and the 8-bit bus
+    Bytes,
+    /// A 16-bit word per `Rgb565` pixel, on the 16-bit bus
+    Words,
+}
+
+/// Returns the words of the bus configured to carry the `C` pixels, `None` when the bus cannot
+/// carry them.
+fn pixel_format<C: ColourMode>(config: &Config) -> Option<PixelFormat> {
+    match config.parallel_interface {
+        ParallelInterface::Bits8 => Some(PixelFormat::Bytes),
+        ParallelInterface::Bits16 if size_of::<C::Bytes>() == 2 => Some(PixelFormat::Words),
+        _ => None,
+    }
+}
+
+/// Returns the Rgb565 pixels of `bytes` as the words of the 16-bit bus.
+pub(crate) fn pixel_words(bytes: &[u8]) -> impl Iterator<Item = u16> + '_ {
+    bytes
+        .chunks_exact(2)
+        .map(|pixel| u16::from_be_bytes([pixel[0], pixel[1]]))
+}
+
+/// Pixels kept by a driver, the frame buffer of the buffered drivers.
+pub(crate) trait Pixels {
+    /// Follows the rotation of the image.
+    fn set_orientation(&mut self, orientation: Orientation);
+
+    /// Marks the GDDRAM content as unknown.
+    fn invalidate_gddram(&mut self);
+}
+
+impl Pixels for () {
+    fn set_orientation(&mut self, _orientation: Orientation) {}
+
+    fn invalidate_gddram(&mut self) {}
+}
+
+/// Settings and state of the controller of a `WIDTH` x `HEIGHT` panel, with the pixels `P` kept
+/// by the driver.
+pub(crate) struct Controller<const WIDTH: usize, const HEIGHT: usize, P> {
+    pub(crate) config: Config,
+    pub(crate) state: State,
+    pub(crate) pixels: P,
+    pixel_format: Option<PixelFormat>,
+    orientation: Orientation,
+    scrolling: bool,
+}
+
+impl<const WIDTH: usize, const HEIGHT: usize, P: Pixels> Controller<WIDTH, HEIGHT, P> {
+    // Fails the build when the panel does not fit in the GDDRAM
+    const GEOMETRY_CHECK: () = assert!(
+        WIDTH > 0 && WIDTH <= GDDRAM_WIDTH && HEIGHT > 0 && HEIGHT <= GDDRAM_HEIGHT,
+        "the panel geometry must be between 1x1 and 128x128"
+    );
+
+    /// Creates the controller with the settings applied by `turn_on`, in the colour depth of `C`.
+    pub(crate) fn new<C: ColourMode>(config: Config, pixels: P) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::GEOMETRY_CHECK;
+
+        Self {
+            config: config.remap(colour::with_depth::<C>(config.remap)),
+            state: State::Uninit,
+            pixels,
+            pixel_format: pixel_format::<C>(&config),
+            orientation: Orientation::default(),
+            scrolling: false,
+        }
+    }
+
+    /// Replaces the pixels kept by the driver.
+    pub(crate) fn map_pixels<Q, F>(self, f: F) -> Controller<WIDTH, HEIGHT, Q>
+    where
+        F: FnOnce(P) -> Q,
+    {
+        Controller {
+            config: self.config,
+            state: self.state,
+            pixels: f(self.pixels),
+            pixel_format: self.pixel_format,
+            orientation: self.orientation,
+            scrolling: self.scrolling,
+        }
+    }
+
+    /// Returns the dimensions of the rotated image.
+    pub(crate) fn dimensions(&self) -> (usize, usize) {
+        self.orientation.dimensions(WIDTH, HEIGHT)
+    }
+
+    /// Returns the rotation of the image.
+    pub(crate) fn rotation(&self) -> Rotation {
+        self.orientation.rotation
+    }
+
+    /// Returns the rows the image is scrolled by.
+    pub(crate) fn scroll_offset(&self) -> u8 {
+        self.orientation.scroll
+    }
+
+    /// Returns the words of the bus carrying the pixels.
+    pub(crate) fn pixel_format(&self) -> Result<PixelFormat, Error> {
+        // turn_on rejects the buses which cannot carry the pixels
+        self.pixel_format.ok_or(Error::InvalidArgument)
+    }
+
+    /// Returns true while the horizontal scroll runs.
+    pub(crate) fn is_scrolling(&self) -> bool {
+        self.scrolling
+    }
+
+    /// Records the reset of the controller, which stops the scroll.
+    pub(crate) fn reset(&mut self) {
+        self.state = State::Reset;
+        self.scrolling = false;
+    }
+
+    /// Returns the commands initializing the display, or turning it back on once turned off.
+    ///
+    /// Every command is validated before any is sent, call `turned_on` once they were sent.
+    pub(crate) fn turn_on(&self) -> Result<impl Iterator<Item = Command> + '_, Error> {
+        if self.state == State::Uninit {
+            return Err(Error::NotInitialized);
+        }
+        // The presets hold settings of their geometry, such as the start line
+        if usize::from(self.config.width) != WIDTH || usize::from(self.config.height) != HEIGHT {
+            return Err(Error::InvalidArgument);
+        }
+        self.pixel_format()?;
+        for command in self.turn_on_sequence() {
+            command.validate()?;
+        }
+        Ok(self.turn_on_sequence())
+    }
+
+    /// Returns the commands of `turn_on`, the wake command when the display is turned off.
+    fn turn_on_sequence(&self) -> impl Iterator<Item = Command> + '_ {
+        let initialize = self.state != State::Sleep;
+        let (stop, wake, sequence) = if initialize {
+            (
+                self.stop(),
+                None,
+                Some(self.orientation.init_sequence(&self.config)),
+            )
+        } else {
+            (None, Some(Command::DisplayOn), None)
+        };
+        stop.into_iter()
+            .chain(wake)
+            .chain(sequence.into_iter().flatten())
+    }
+
+    /// Records the display turned on by the commands of `turn_on`.
+    pub(crate) fn turned_on(&mut self) {
+        if self.state != State::Sleep {
+            self.scrolling = false;
+            // The GDDRAM content is undefined until the whole frame buffer is sent
+            self.pixels.invalidate_gddram();
+        }
+        self.state = State::On;
+    }
+
+    /// Returns the command turning off the display, the GDDRAM content is kept.
+    ///
+    /// Call `turned_off` once it was sent.
+    pub(crate) fn turn_off(&self) -> Result<Command, Error> {
+        self.state.check_initialized()?;
+        Ok(Command::DisplayOff)
+    }
+
+    /// Records the display turned off by the command of `turn_off`.
+    pub(crate) fn turned_off(&mut self) {
+        self.state = State::Sleep;
+    }
+
+    /// Rotates the image on the panel.
+    pub(crate) fn set_rotation(&mut self, rotation: Rotation) -> Commands<2> {
+        self.orientation.rotation = rotation;
+        self.orientation_changed()
+    }
+
+    /// Mirrors the image horizontally and/or vertically, after the rotation.
+    pub(crate) fn set_mirror(&mut self, horizontal: bool, vertical: bool) -> Commands<2> {
+        self.orientation.mirror = (horizontal, vertical);
+        self.orientation_changed()
+    }
+
+    fn orientation_changed(&mut self) -> Commands<2> {
+        self.pixels.set_orientation(self.orientation);
+        self.when_initialized([
+            Some(Command::SetRemap(self.orientation.remap(&self.config))),
+            Some(Command::DisplayStartLine(
+                self.orientation.start_line(&self.config),
+            )),
+        ])
+    }
+
+    /// Sets the master contrast current (0 - 15).
+    pub(crate) fn set_brightness(&mut self, level: u8) -> Result<Command, Error> {
+        self.state.check_initialized()?;
+        let command = Command::MasterContrast(level);
+        command.validate()?;
+        self.config = self.config.master_contrast(level);
+        Ok(command)
+    }
+
+    /// Sets the contrast current of the red, green and blue channels.
+    pub(crate) fn set_channel_contrast(
+        &mut self,
+        red: u8,
+        green: u8,
+        blue: u8,
+    ) -> Result<Command, Error> {
+        self.state.check_initialized()?;
+        self.config = self.config.channel_contrast(red, green, blue);
+        Ok(self.config.contrast_command())
+    }
+
+    /// Scrolls the image up by `offset` rows (0 - 127), with the display start line.
+    pub(crate) fn set_scroll_offset(&mut self, offset: u8) -> Result<Command, Error> {
+        self.state.check_initialized()?;
+        Self::check_scroll_offset(offset)?;
+        Ok(self.scroll_to(offset))
+    }
+
+    /// Returns the frame buffer row shown at the row `row` of the scrolled image.
+    pub(crate) fn wrapped_row(&self, row: u32) -> u32 {
+        (row + u32::from(self.orientation.scroll)) % HEIGHT as u32
+    }
+
+    /// Fails with `Error::InvalidArgument` unless the image can be scrolled by `offset` rows.
+    fn check_scroll_offset(offset: u8) -> Result<(), Error> {
+        // The start line of shorter panels would show GDDRAM rows outside of the frame buffer
+        if usize::from(offset) < GDDRAM_HEIGHT && (HEIGHT == GDDRAM_HEIGHT || offset == 0) {
+            Ok(())
+        } else {
+            Err(Error::InvalidArgument)
+        }
+    }
+
+    /// Returns the start line command scrolling the image by `offset` rows.
+    fn scroll_to(&mut self, offset: u8) -> Command {
+        self.orientation.scroll = offset;
+        Command::DisplayStartLine(self.orientation.start_line(&self.config))
+    }
+
+    /// Starts the horizontal scroll of the panel `rows`, by `step` columns every `speed` interval.
+    ///
+    /// Call `scroll_started` once the commands were sent.
+    pub(crate) fn start_horizontal_scroll(
+        &self,
+        rows: Range<u8>,
+        step: u8,
+        speed: ScrollSpeed,
+    ) -> Result<Commands<3>, Error> {
+        self.state.check_initialized()?;
+        if rows.is_empty() || rows.end > self.config.height || step == 0 {
+            return Err(Error::InvalidArgument);
+        }
+
+        // The scroll must be stopped to change its settings
+        Ok(commands([
+            self.stop(),
+            Some(Command::HorizontalScroll {
+                shift: step,
+                start_row: rows.start,
+                rows: rows.end - rows.start,
+                speed,
+            }),
+            Some(Command::StartScroll),
+        ]))
+    }
+
+    /// Records the horizontal scroll started by the commands of `start_horizontal_scroll`.
+    pub(crate) fn scroll_started(&mut self) {
+        self.scroll_stopped();
+        self.scrolling = true;
+    }
+
+    /// Stops the horizontal scroll.
+    ///
+    /// Call `scroll_stopped` once the commands were sent.
+    pub(crate) fn stop_scroll(&self) -> Result<Commands<1>, Error> {
+        self.state.check_initialized()?;
+        Ok(commands([self.stop()]))
+    }
+
+    /// Returns the command stopping the horizontal scroll when it runs.
+    fn stop(&self) -> Option<Command> {
+        if self.scrolling {
+            Some(Command::StopScroll)
+        } else {
+            None
+        }
+    }
+
+    /// Records the horizontal scroll stopped by the commands of `stop_scroll`, `flush`,
+    /// `flush_region` or `scroll_up`.
+    pub(crate) fn scroll_stopped(&mut self) {
+        if self.scrolling {
+            self.scrolling = false;
+            // The scroll leaves the GDDRAM content undefined
+            self.pixels.invalidate_gddram();
+        }
+    }
+
+    /// Returns the commands selecting the GDDRAM window written by the next pixels, in image
+    /// coordinates.
+    pub(crate) fn address_window(&self, window: DirtyArea) -> Result<Commands<3>, Error> {
+        self.state.check_initialized()?;
+        let [columns, rows] = self.orientation.address_window(
+            &self.config,
+            window.left,
+            window.top,
+            window.right,
+            window.bottom,
+        );
+        Ok(commands([
+            Some(columns),
+            Some(rows),
+            Some(Command::WriteRam),
+        ]))
+    }
+
+    /// Returns `commands` once the display is initialized, they are sent by `turn_on` otherwise.
+    fn when_initialized<const N: usize>(&self, commands: [Option<Command>; N]) -> Commands<N> {
+        let commands = if self.state.is_initialized() {
+            commands
+        } else {
+            [None; N]
+        };
+        IntoIterator::into_iter(commands).flatten()
+    }
+}
+
+impl<const WIDTH: usize, const HEIGHT: usize, S, C>
+    Controller<WIDTH, HEIGHT, Frame<WIDTH, HEIGHT, S, C>>
+where
+    S: Shadow<C>,
+    C: ColourMode,
+{
+    /// Returns the commands sent before the windows of `Frame::flush_windows`.
+    pub(crate) fn flush(&self) -> Result<Commands<2>, Error> {
+        self.state.check_initialized()?;
+        Ok(commands([
+            self.stop(),
+            Some(Command::DisplayStartLine(
+                self.orientation.start_line(&self.config),
+            )),
+        ]))
+    }
+
+    /// Returns the commands and the window flushing the frame buffer within `area`.
+    pub(crate) fn flush_region(
+        &self,
+        area: &Rectangle,
+    ) -> Result<(Commands<1>, Option<DirtyArea>), Error> {
+        self.state.check_initialized()?;
+        Ok((commands([self.stop()]), self.pixels.clip(area)))
+    }
+
+    /// Scrolls the image up by `rows`, returns the commands and the windows of the frame buffer
+    /// rows exposed at the bottom.
+    pub(crate) fn scroll_up(
+        &mut self,
+        rows: u8,
+    ) -> Result<(Commands<2>, impl Iterator<Item = DirtyArea>), Error> {
+        self.state.check_initialized()?;
+        if usize::from(rows) >= GDDRAM_HEIGHT {
+            return Err(Error::InvalidArgument);
+        }
+
+        let offset = (usize::from(self.scroll_offset()) + usize::from(rows)) % GDDRAM_HEIGHT;
+        Self::check_scroll_offset(offset as u8)?;
+
+        let top = self.wrapped_row(0);
+        let commands = commands([self.stop(), Some(self.scroll_to(offset as u8))]);
+
+        // The exposed rows wrap around the end of the frame buffer
+        let rows = u32::from(rows);
+        let first = rows.min(HEIGHT as u32 - top);
+        let windows = [self.lines(top, first), self.lines(0, rows - first)];
+        Ok((commands, IntoIterator::into_iter(windows).flatten()))
+    }
+
+    /// Returns the window of `count` frame buffer rows from `start`, or columns when the rotation
+    /// swaps the dimensions.
+    fn lines(&self, start: u32, count: u32) -> Option<DirtyArea> {
+        let (width, height) = self.dimensions();
+        let area = if self.orientation.rotation.is_transposed() {
+            Rectangle::new(Point::new(start as i32, 0), Size::new(count, height as u32))
+        } else {
+            Rectangle::new(Point::new(0, start as i32), Size::new(width as u32, count))
+        };
+        self.pixels.clip(&area)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_graphics::pixelcolor::{Rgb565, Rgb666};
+
+    #[test]
+    fn invalid_sequence_leaves_state_unchanged() {
+        let mut controller =
+            Controller::<128, 128, ()>::new::<Rgb565>(Config::new().mux_ratio(200), ());
+        controller.reset();
+        assert!(matches!(controller.turn_on(), Err(Error::InvalidArgument)));
+        assert_eq!(controller.state, State::Reset);
+        assert!(matches!(
+            controller.address_window(DirtyArea::pixel(0, 0)),
+            Err(Error::NotInitialized)
+        ));
+    }
+
+    #[test]
+    fn turn_on_rejects_bus_without_pixel_format() {
+        let bus = |interface| Config::new().parallel_interface(interface);
+        let turn_on = |mut controller: Controller<128, 128, ()>| {
+            controller.reset();
+            controller.turn_on().map(|_| ())
+        };
+        let rgb565 = |interface| Controller::new::<Rgb565>(bus(interface), ());
+        let rgb666 = |interface| Controller::new::<Rgb666>(bus(interface), ());
+        assert!(turn_on(rgb565(ParallelInterface::Bits16)).is_ok());
+        assert!(turn_on(rgb666(ParallelInterface::Bits8)).is_ok());
+        assert!(matches!(
+            turn_on(rgb666(ParallelInterface::Bits16)),
+            Err(Error::InvalidArgument)
+        ));
+        assert!(matches!(
+            turn_on(rgb565(ParallelInterface::Bits18)),
+            Err(Error::InvalidArgument)
+        ));
+    }
+
+    #[test]
+    fn contrast_needs_initialized_display() {
+        let mut controller = Controller::<128, 128, ()>::new::<Rgb565>(Config::new(), ());
+        assert!(matches!(
+            controller.set_brightness(8),
+            Err(Error::NotInitialized)
+        ));
+        assert!(matches!(
+            controller.set_channel_contrast(1, 2, 3),
+            Err(Error::NotInitialized)
+        ));
+        controller.reset();
+        controller.turned_on();
+        assert!(matches!(
+            controller.set_brightness(8),
+            Ok(Command::MasterContrast(8))
+        ));
+    }
+
+    #[test]
+    fn scroll_offset_needs_initialized_display() {
+        let mut controller = Controller::<128, 128, ()>::new::<Rgb565>(Config::new(), ());
+        assert!(matches!(
+            controller.set_scroll_offset(8),
+            Err(Error::NotInitialized)
+        ));
+        assert_eq!(controller.scroll_offset(), 0);
+    }
+
+    #[test]
+    fn turn_on_wakes_without_initializing() {
+        let mut controller = Controller::<128, 128, ()>::new::<Rgb565>(Config::new(), ());
+        controller.reset();
+        let sequence = controller.config.init_sequence().count();
+        assert_eq!(
+            controller.turn_on().map(Iterator::count).ok(),
+            Some(sequence)
+        );
+        controller.turned_on();
+        controller.turned_off();
+        assert_eq!(controller.state, State::Sleep);
+        let wake: Vec<_> = controller.turn_on().unwrap().collect();
+        assert!(matches!(wake[..], [Command::DisplayOn]));
+        controller.turned_on();
+        assert_eq!(controller.state, State::On);
+    }
+}
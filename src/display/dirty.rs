@@ -0,0 +1,63 @@
+//! Tracking of the frame buffer area changed since the last flush
+
+/// Bounding box of the changed pixels, in image coordinates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct DirtyArea {
+    pub(crate) left: usize,
+    pub(crate) top: usize,
+    pub(crate) right: usize,
+    pub(crate) bottom: usize,
+}
+
+impl DirtyArea {
+    /// Creates the area between two corners, both included.
+    pub(crate) fn new(left: usize, top: usize, right: usize, bottom: usize) -> Self {
+        Self {
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+
+    /// Creates the area of a single pixel.
+    pub(crate) fn pixel(x: usize, y: usize) -> Self {
+        Self::new(x, y, x, y)
+    }
+
+    /// Returns the smallest area containing both areas.
+    pub(crate) fn union(self, other: Self) -> Self {
+        Self {
+            left: self.left.min(other.left),
+            top: self.top.min(other.top),
+            right: self.right.max(other.right),
+            bottom: self.bottom.max(other.bottom),
+        }
+    }
+
+    /// Returns the number of pixels of the area.
+    pub(crate) fn pixel_count(self) -> usize {
+        (self.right - self.left + 1) * (self.bottom - self.top + 1)
+    }
+}
+
+/// Accumulates the areas changed since the last flush.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct DirtyTracker {
+    area: Option<DirtyArea>,
+}
+
+impl DirtyTracker {
+    /// Adds an area to the tracker.
+    pub(crate) fn mark(&mut self, area: DirtyArea) {
+        self.area = Some(match self.area {
+            Some(dirty) => dirty.union(area),
+            None => area,
+        });
+    }
+
+    /// Returns the changed area and resets the tracker.
+    pub(crate) fn take(&mut self) -> Option<DirtyArea> {
+        self.area.take()
+    }
+}
@@ -1,166 +1,575 @@
 //! main display module
-use crate::display::command::Command;
-use display_interface::{DataFormat::U8, DisplayError, WriteOnlyDataCommand};
+use crate::display::{
+    colour::ColourMode,
+    command::{Command, ScrollSpeed},
+    config::Config,
+    controller::{pixel_words, Controller, PixelFormat},
+    dirty::DirtyArea,
+    frame::Frame,
+    rotation::Rotation,
+    shadow::{FrameShadow, NoShadow, Shadow},
+    state::State,
+};
+use crate::Error;
+use core::ops::Range;
+use display_interface::{
+    DataFormat::{U16BEIter, U8},
+    WriteOnlyDataCommand,
+};
 use embedded_graphics::{
     draw_target::DrawTarget,
-    geometry::OriginDimensions,
-    geometry::Size,
-    pixelcolor::{
-        raw::{RawData, RawU16},
-        Rgb565,
-    },
+    geometry::{OriginDimensions, Size},
+    pixelcolor::Rgb565,
+    primitives::Rectangle,
     Pixel,
 };
-use embedded_hal::blocking::delay::DelayMs;
-use embedded_hal::digital::v2::OutputPin;
+use embedded_hal::{delay::DelayNs, digital::OutputPin};
 
-const DISPLAY_WIDTH: usize = 128;
-const DISPLAY_HEIGHT: usize = 128;
-const BUFFER_SIZE: usize = DISPLAY_WIDTH * DISPLAY_HEIGHT * 2;
+// Size of the controller GDDRAM
+pub(crate) const GDDRAM_WIDTH: usize = 128;
+pub(crate) const GDDRAM_HEIGHT: usize = 128;
+// Size of the chunks sent to the display interface
+pub(crate) const CHUNK_SIZE: usize = 128 * 8 * 2;
 
 /// Represents the SSD1351 Display.
 ///
-/// Use this struct to initialize the driver.
-pub struct Ssd1351<DI> {
+/// Use this struct to initialize the driver. `WIDTH` and `HEIGHT` are the geometry of the panel,
+/// at most 128x128, and size the frame buffer:
+///
+/// ```ignore
+/// let mut disp: Ssd1351<_, 128, 96> = Ssd1351::with_config(spii, Config::adafruit_128x96());
+/// ```
+///
+/// `S` holds the copy of the GDDRAM content used by the frame diff flush, see `with_frame_diff`.
+///
+/// `C` is the colour of the frame buffer, `Rgb565` or `Rgb666` for the 262k colour mode:
+///
+/// ```ignore
+/// let mut disp: Ssd1351<_, 128, 128, NoShadow, Rgb666> =
+///     Ssd1351::with_config(spii, Config::default());
+/// ```
+pub struct Ssd1351<
+    DI,
+    const WIDTH: usize = 128,
+    const HEIGHT: usize = 128,
+    S = NoShadow,
+    C: ColourMode = Rgb565,
+> {
     display: DI,
-    buffer: [u8; BUFFER_SIZE],
+    controller: Controller<WIDTH, HEIGHT, Frame<WIDTH, HEIGHT, S, C>>,
 }
 
-
 impl<DI: WriteOnlyDataCommand> Ssd1351<DI> {
-    /// Creates the SSD1351 Display.
+    /// Creates the 128x128 SSD1351 Display.
     ///
     /// Make sure to reset and initialize the display before use!
     pub fn new(display: DI) -> Self {
+        Self::with_config(display, Config::default())
+    }
+}
+
+impl<DI, const WIDTH: usize, const HEIGHT: usize, C> Ssd1351<DI, WIDTH, HEIGHT, NoShadow, C>
+where
+    DI: WriteOnlyDataCommand,
+    C: ColourMode,
+{
+    /// Creates the SSD1351 Display with the settings applied by `turn_on`.
+    ///
+    /// Use one of the `Config` presets to match the module. Its geometry must be `WIDTH` x
+    /// `HEIGHT`, `turn_on` fails with `Error::InvalidArgument` otherwise. The colour depth is
+    /// replaced by the one of `C`.
+    ///
+    /// The geometry is not inferred from the configuration: name the type of the display, the
+    /// defaults of the type parameters only apply to an annotation.
+    ///
+    /// ```ignore
+    /// let mut disp: Ssd1351<_> = Ssd1351::with_config(spii, Config::adafruit_128x128());
+    /// ```
+    ///
+    /// Make sure to reset and initialize the display before use!
+    pub fn with_config(display: DI, config: Config) -> Self {
         Self {
             display,
-            buffer: [0; BUFFER_SIZE],
+            controller: Controller::new::<C>(config, Frame::new(NoShadow, config.byte_order)),
         }
     }
 
+    /// Enables the frame diff flush.
+    ///
+    /// The display keeps a copy of the pixels sent to the controller, and `flush` compares the frame
+    /// buffer with it row by row to send only the changed spans. This suits images redrawn from
+    /// scratch every frame, at the cost of a second frame buffer. The whole changed area is sent
+    /// when most of it differs.
+    pub fn with_frame_diff(self) -> Ssd1351<DI, WIDTH, HEIGHT, FrameShadow<WIDTH, HEIGHT, C>, C> {
+        Ssd1351 {
+            display: self.display,
+            controller: self
+                .controller
+                .map_pixels(|frame| frame.with_shadow(FrameShadow::default())),
+        }
+    }
+}
+
+impl<DI, const WIDTH: usize, const HEIGHT: usize, S, C> Ssd1351<DI, WIDTH, HEIGHT, S, C>
+where
+    DI: WriteOnlyDataCommand,
+    S: Shadow<C>,
+    C: ColourMode,
+{
     /// Resets the display.
     pub fn reset<RST, DELAY>(
         &mut self,
         rst: &mut RST,
         delay: &mut DELAY,
-    ) -> Result<(), DisplayError>
+    ) -> Result<(), Error<RST::Error>>
     where
         RST: OutputPin,
-        DELAY: DelayMs<u8>,
+        DELAY: DelayNs,
     {
-        // rst.set_high().map_err(|_| DisplayError::BusWriteError)?;
-        // delay.delay_ms(100);
+        hard_reset(rst, delay)?;
+        self.controller.reset();
+        Ok(())
+    }
+
+    /// Marks the display as reset, for boards without a reset pin.
+    ///
+    /// The controller resets itself at power on.
+    pub fn assume_reset(&mut self) {
+        self.controller.reset();
+    }
+
+    /// Initializes the display with the settings of its configuration.
+    ///
+    /// The display must be reset first. A display turned off is only turned back on, the GDDRAM
+    /// content is kept.
+    pub fn turn_on(&mut self) -> Result<(), Error> {
+        send_commands(&mut self.display, self.controller.turn_on()?)?;
+        self.controller.turned_on();
+        Ok(())
+    }
+
+    /// Returns the lifecycle state of the display.
+    pub fn state(&self) -> State {
+        self.controller.state
+    }
+
+    /// Rotates the image on the panel.
+    ///
+    /// The dimensions of the draw target are swapped by `Deg90` and `Deg270`, redraw the frame
+    /// buffer before the next flush.
+    pub fn set_rotation(&mut self, rotation: Rotation) -> Result<(), Error> {
+        send_commands(&mut self.display, self.controller.set_rotation(rotation))
+    }
+
+    /// Mirrors the image horizontally and/or vertically, after the rotation.
+    pub fn set_mirror(&mut self, horizontal: bool, vertical: bool) -> Result<(), Error> {
+        send_commands(
+            &mut self.display,
+            self.controller.set_mirror(horizontal, vertical),
+        )
+    }
+
+    /// Returns the rotation of the image.
+    pub fn rotation(&self) -> Rotation {
+        self.controller.rotation()
+    }
+
+    /// Sets the brightness with the master contrast current, from 0 to 15.
+    ///
+    /// The current of the three colours is scaled by `(level + 1) / 16`. The display must be
+    /// initialized, `Config::master_contrast` sets the brightness applied by `turn_on`.
+    pub fn set_brightness(&mut self, level: u8) -> Result<(), Error> {
+        let command = self.controller.set_brightness(level)?;
+        self.send_command(command)
+    }
+
+    /// Sets the contrast current of the red, green and blue channels, to balance the colours.
+    ///
+    /// The display must be initialized, `Config::channel_contrast` sets the contrast applied by
+    /// `turn_on`.
+    pub fn set_channel_contrast(&mut self, red: u8, green: u8, blue: u8) -> Result<(), Error> {
+        let command = self.controller.set_channel_contrast(red, green, blue)?;
+        self.send_command(command)
+    }
+
+    /// Scrolls the image up by `offset` rows (0 - 127), with the display start line.
+    ///
+    /// The rows wrap around the 128 GDDRAM rows: the image row `y` shows the frame buffer row
+    /// `wrapped_row(y)`. The image scrolls left instead when the rotation swaps its dimensions.
+    ///
+    /// Only panels with 128 rows can scroll, the start line of shorter ones would show GDDRAM rows
+    /// outside of the frame buffer: offsets other than 0 fail with `Error::InvalidArgument`.
+    ///
+    /// Fails with `Error::NotInitialized` before `turn_on`.
+    pub fn set_scroll_offset(&mut self, offset: u8) -> Result<(), Error> {
+        let command = self.controller.set_scroll_offset(offset)?;
+        self.send_command(command)
+    }
 
-        rst.set_low().map_err(|_| DisplayError::BusWriteError)?;
-        delay.delay_ms(100);
+    /// Returns the rows the image is scrolled by.
+    pub fn scroll_offset(&self) -> u8 {
+        self.controller.scroll_offset()
+    }
 
-        rst.set_high().map_err(|_| DisplayError::BusWriteError)?;
-        delay.delay_ms(100);
+    /// Returns the frame buffer row shown at the row `row` of the scrolled image, or the column
+    /// when the rotation swaps the dimensions.
+    pub fn wrapped_row(&self, row: u32) -> u32 {
+        self.controller.wrapped_row(row)
+    }
+
+    /// Scrolls the image up by `rows`, and sends only the frame buffer rows exposed at the bottom.
+    ///
+    /// The exposed rows are the ones which scrolled off the top, from `wrapped_row(0)`: draw the
+    /// new content there before the call. A log scrolls by one line of text with a start line
+    /// command and the transfer of the line.
+    ///
+    /// Fails with `Error::InvalidArgument` on panels with fewer than 128 rows, see
+    /// `set_scroll_offset`.
+    pub fn scroll_up(&mut self, rows: u8) -> Result<(), Error> {
+        let (commands, windows) = self.controller.scroll_up(rows)?;
+        send_commands(&mut self.display, commands)?;
+        self.controller.scroll_stopped();
+        for window in windows {
+            self.send_window(window)?;
+        }
+        Ok(())
+    }
 
+    /// Turns off the display, the GDDRAM content is kept.
+    pub fn turn_off(&mut self) -> Result<(), Error> {
+        let command = self.controller.turn_off()?;
+        self.send_command(command)?;
+        self.controller.turned_off();
         Ok(())
     }
 
-    /// Initializes the display.
-    pub fn turn_on(&mut self) -> Result<(), DisplayError> {
-        self.send_command(Command::Unlock0x12)?;
-        self.send_command(Command::Unlock0xB1)?;
-        self.send_command(Command::DisplayOff)?;
-        self.send_command(Command::ClockDivider)?;
-        self.send_command(Command::Muxratio)?;
-        self.send_command(Command::ColumnAddress)?;
-        self.send_command(Command::RowAddress)?;
-        self.send_command(Command::SegmentRemapping)?;
-        self.send_command(Command::DisplayStartLine)?;
-        self.send_command(Command::DisplayOffset)?;
-        self.send_command(Command::SetGpio)?;
-        self.send_command(Command::SelectInternalDiodeDrop)?;
-        self.send_command(Command::Precharge)?;
-        self.send_command(Command::SetSegmentLowVoltage)?;
-        self.send_command(Command::SetVcomHVoltage)?;
-        //self.send_command(Command::Invert)?;
-        self.send_command(Command::ContrastMaster)?;
-        self.send_command(Command::Precharge2)?;
-        self.send_command(Command::Contrast)?;
-        self.send_command(Command::DisplayOn)?;
-        self.send_command(Command::NomalDisplay)?;
-        self.send_command(Command::DisplayOffset)?;
+    /// Starts the horizontal scroll of the panel `rows`, by `step` columns every `speed` interval.
+    ///
+    /// `step` is 1 - 63 to scroll towards SEG127, 64 - 255 towards SEG0. The controller moves the
+    /// image without transfers until `stop_scroll` or the next flush, which stops the scroll first.
+    pub fn start_horizontal_scroll(
+        &mut self,
+        rows: Range<u8>,
+        step: u8,
+        speed: ScrollSpeed,
+    ) -> Result<(), Error> {
+        let commands = self.controller.start_horizontal_scroll(rows, step, speed)?;
+        send_commands(&mut self.display, commands)?;
+        self.controller.scroll_started();
         Ok(())
     }
 
-    /// Turns off the display.
-    pub fn turn_off(&mut self) -> Result<(), DisplayError> {
-        self.send_command(Command::DisplayOff)?;
+    /// Stops the horizontal scroll.
+    ///
+    /// The scroll leaves the GDDRAM content undefined, the next flush sends the whole frame buffer.
+    pub fn stop_scroll(&mut self) -> Result<(), Error> {
+        send_commands(&mut self.display, self.controller.stop_scroll()?)?;
+        self.controller.scroll_stopped();
         Ok(())
     }
 
+    /// Returns true while the horizontal scroll runs.
+    pub fn is_scrolling(&self) -> bool {
+        self.controller.is_scrolling()
+    }
+
     /// Allows to send custom commands to the display.
-    pub fn send_command(&mut self, command: Command) -> Result<(), DisplayError> {
+    pub fn send_command(&mut self, command: Command) -> Result<(), Error> {
         command.send(&mut self.display)
     }
 
     /// Flushes the display, and makes the output visible on the screen.
-    pub fn flush(&mut self) -> Result<(), DisplayError> {
-        self.send_command(Command::ColumnAddress)?;
-        self.send_command(Command::RowAddress)?;
-        self.send_command(Command::DisplayStartLine)?;
-        self.send_command(Command::WriteRam)?;
-
-        // Number of bytes 128*128*2;
-        let multiplier = 128 * 8 * 2;
-        for i in 0..16 {
-            self.display
-                .send_data(U8(&self.buffer[i * multiplier..(i + 1) * multiplier]));
+    ///
+    /// Only the bounding box of the pixels drawn since the last flush is sent, or the changed spans
+    /// within it when the frame diff flush is enabled.
+    ///
+    /// A running horizontal scroll is stopped first, and the whole frame buffer is sent.
+    pub fn flush(&mut self) -> Result<(), Error> {
+        send_commands(&mut self.display, self.controller.flush()?)?;
+        self.controller.scroll_stopped();
+        let mut windows = self.controller.pixels.flush_windows();
+        while let Some(window) = self.controller.pixels.next_window(&mut windows) {
+            self.send_window(window)?;
+        }
+        Ok(())
+    }
+
+    /// Flushes the part of the frame buffer within `area`, and makes it visible on the screen.
+    ///
+    /// Only the rows of the area are sent, which saves bandwidth when a small part of the image
+    /// changed. The area is clipped to the display.
+    pub fn flush_region(&mut self, area: Rectangle) -> Result<(), Error> {
+        let (commands, window) = self.controller.flush_region(&area)?;
+        send_commands(&mut self.display, commands)?;
+        self.controller.scroll_stopped();
+        match window {
+            Some(window) => self.send_window(window),
+            None => Ok(()),
         }
-        self.display.send_data(U8(&self.buffer))
     }
+
+    /// Sends the pixels of the frame buffer within `window`.
+    fn send_window(&mut self, window: DirtyArea) -> Result<(), Error> {
+        let format = self.controller.pixel_format()?;
+        send_commands(&mut self.display, self.controller.address_window(window)?)?;
+        for bytes in self.controller.pixels.window_data(window) {
+            send_pixels(&mut self.display, format, bytes)?;
+        }
+        self.controller.pixels.window_sent(window);
+        Ok(())
+    }
+}
+
+/// Pulses the reset pin of the display.
+pub(crate) fn hard_reset<RST, DELAY>(
+    rst: &mut RST,
+    delay: &mut DELAY,
+) -> Result<(), Error<RST::Error>>
+where
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    // rst.set_high().map_err(Error::Pin)?;
+    // delay.delay_ms(100);
+
+    rst.set_low().map_err(Error::Pin)?;
+    delay.delay_ms(100);
+
+    rst.set_high().map_err(Error::Pin)?;
+    delay.delay_ms(100);
+
+    Ok(())
+}
+
+/// Sends the commands in order.
+pub(crate) fn send_commands<DI, I>(display: &mut DI, commands: I) -> Result<(), Error>
+where
+    DI: WriteOnlyDataCommand,
+    I: IntoIterator<Item = Command>,
+{
+    for command in commands {
+        command.send(display)?;
+    }
+    Ok(())
 }
 
-impl<DI> DrawTarget for Ssd1351<DI>
+/// Sends the bytes of the pixels in the words of the bus, see `Controller::pixel_format`.
+pub(crate) fn send_pixels<DI>(
+    display: &mut DI,
+    format: PixelFormat,
+    bytes: &[u8],
+) -> Result<(), Error>
 where
     DI: WriteOnlyDataCommand,
 {
-    type Color = Rgb565;
-    type Error = DisplayError;
+    match format {
+        PixelFormat::Bytes => display.send_data(U8(bytes))?,
+        PixelFormat::Words => display.send_data(U16BEIter(&mut pixel_words(bytes)))?,
+    }
+    Ok(())
+}
+
+impl<DI, const WIDTH: usize, const HEIGHT: usize, S, C> DrawTarget
+    for Ssd1351<DI, WIDTH, HEIGHT, S, C>
+where
+    DI: WriteOnlyDataCommand,
+    S: Shadow<C>,
+    C: ColourMode,
+{
+    type Color = C;
+    type Error = Error;
 
     fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
     where
         I: IntoIterator<Item = Pixel<Self::Color>>,
     {
-        pixels.into_iter().for_each(|Pixel(point, colour_pixel)| {
-            let colour_u16 = RawU16::from(colour_pixel).into_inner();
-            let colour = [(colour_u16 >> 8) as u8, colour_u16 as u8];
-            let idx = (point.x + point.y * 128) * 2;
-            // ignore out-of-bounds drawing
-            if idx < 0 || idx >= self.buffer.len() as i32 {
-                return;
-            }
-            let idx = idx as usize;
-            self.buffer[idx] = colour[0];
-            self.buffer[idx + 1] = colour[1];
-        });
+        self.controller
+            .pixels
+            .draw_iter(pixels)
+            .map_err(|never| match never {})
+    }
 
-        Ok(())
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        self.controller
+            .pixels
+            .fill_contiguous(area, colors)
+            .map_err(|never| match never {})
     }
 
-    fn clear(&mut self, fill: Rgb565) -> Result<(), Self::Error> {
-        let colour_u16 = RawU16::from(fill).into_inner();
-        let colour = [(colour_u16 >> 8) as u8, colour_u16 as u8];
-        for x in 0..128 {
-            for y in 0..128 {
-                let idx = ((x + y * 128) * 2) as usize;
-                self.buffer[idx] = colour[0];
-                self.buffer[idx + 1] = colour[1];
-            }
-        }
-        Ok(())
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.controller
+            .pixels
+            .fill_solid(area, color)
+            .map_err(|never| match never {})
+    }
+
+    fn clear(&mut self, fill: C) -> Result<(), Self::Error> {
+        self.controller
+            .pixels
+            .clear(fill)
+            .map_err(|never| match never {})
     }
 }
 
-impl<DI> OriginDimensions for Ssd1351<DI>
+impl<DI, const WIDTH: usize, const HEIGHT: usize, S, C> OriginDimensions
+    for Ssd1351<DI, WIDTH, HEIGHT, S, C>
 where
     DI: WriteOnlyDataCommand,
+    S: Shadow<C>,
+    C: ColourMode,
 {
     fn size(&self) -> Size {
-        Size::new(DISPLAY_WIDTH as u32, DISPLAY_HEIGHT as u32)
+        self.controller.pixels.size()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::testing::Gddram;
+    use embedded_graphics::{geometry::Point, pixelcolor::RgbColor, Drawable};
+
+    const FRAME_BYTES: usize = GDDRAM_WIDTH * GDDRAM_HEIGHT * 2;
+
+    type DiffDisplay = Ssd1351<Gddram, 128, 128, FrameShadow<128, 128, Rgb565>, Rgb565>;
+
+    /// Returns a display with the frame diff flush, its whole frame buffer sent.
+    fn display() -> DiffDisplay {
+        let mut display = Ssd1351::new(Gddram::default()).with_frame_diff();
+        display.assume_reset();
+        display.turn_on().unwrap();
+        display.flush().unwrap();
+        display
+    }
+
+    /// Flushes the display, checks that the GDDRAM matches the frame buffer and returns the
+    /// windows and pixel bytes sent.
+    fn flush(display: &mut DiffDisplay) -> (usize, usize) {
+        let (windows, bytes) = (display.display.windows, display.display.pixel_bytes);
+        display.flush().unwrap();
+
+        let (width, height) = display.controller.dimensions();
+        let transposed = display.rotation().is_transposed();
+        let pixels = display.controller.pixels.pixels();
+        for y in 0..height {
+            for x in 0..width {
+                // The image columns are GDDRAM rows once transposed
+                let (column, row) = if transposed { (y, x) } else { (x, y) };
+                assert_eq!(
+                    display.display.pixel(column, row),
+                    pixels[y * width + x],
+                    "pixel ({}, {})",
+                    x,
+                    y
+                );
+            }
+        }
+        (
+            display.display.windows - windows,
+            display.display.pixel_bytes - bytes,
+        )
+    }
+
+    fn pixel(display: &mut DiffDisplay, x: i32, y: i32, colour: Rgb565) {
+        Pixel(Point::new(x, y), colour).draw(display).unwrap();
+    }
+
+    fn fill(display: &mut DiffDisplay, x: i32, y: i32, width: u32, height: u32, colour: Rgb565) {
+        let area = Rectangle::new(Point::new(x, y), Size::new(width, height));
+        display.fill_solid(&area, colour).unwrap();
+    }
+
+    #[test]
+    fn diff_flush_sends_changed_spans() {
+        let mut display = display();
+        pixel(&mut display, 10, 10, Rgb565::RED);
+        pixel(&mut display, 100, 100, Rgb565::BLUE);
+        assert_eq!(flush(&mut display), (2, 4));
+
+        // Pixels redrawn in the same colour are not sent
+        pixel(&mut display, 10, 10, Rgb565::RED);
+        pixel(&mut display, 40, 12, Rgb565::GREEN);
+        assert_eq!(flush(&mut display), (1, 2));
+    }
+
+    #[test]
+    fn diff_flush_merges_close_spans() {
+        let mut display = display();
+        pixel(&mut display, 10, 10, Rgb565::RED);
+        pixel(&mut display, 11, 11, Rgb565::RED);
+        assert_eq!(flush(&mut display), (1, 8));
+
+        // A window costs less than the unchanged pixels of the merged spans
+        pixel(&mut display, 10, 20, Rgb565::BLUE);
+        pixel(&mut display, 14, 21, Rgb565::BLUE);
+        assert_eq!(flush(&mut display), (2, 4));
+    }
+
+    #[test]
+    fn diff_flush_falls_back_to_area() {
+        let mut display = display();
+        fill(&mut display, 20, 20, 10, 10, Rgb565::RED);
+        assert_eq!(flush(&mut display), (1, 200));
+
+        // Three of ten rows changed: the spans cost less than the area
+        fill(&mut display, 20, 20, 10, 10, Rgb565::RED);
+        fill(&mut display, 20, 21, 10, 3, Rgb565::GREEN);
+        assert_eq!(flush(&mut display), (1, 60));
+
+        // Nine of ten rows changed: the whole area is sent
+        fill(&mut display, 20, 20, 10, 10, Rgb565::BLUE);
+        fill(&mut display, 20, 20, 10, 1, Rgb565::RED);
+        assert_eq!(flush(&mut display), (1, 200));
+    }
+
+    #[test]
+    fn rotation_sends_whole_frame() {
+        let mut display = display();
+        display.set_rotation(Rotation::Deg90).unwrap();
+        pixel(&mut display, 10, 100, Rgb565::RED);
+        assert_eq!(flush(&mut display), (1, FRAME_BYTES));
+
+        pixel(&mut display, 20, 30, Rgb565::RED);
+        assert_eq!(flush(&mut display), (1, 2));
+    }
+
+    #[test]
+    fn horizontal_scroll_sends_whole_frame() {
+        let mut display = display();
+        display
+            .start_horizontal_scroll(0..8, 1, ScrollSpeed::Normal)
+            .unwrap();
+        pixel(&mut display, 10, 10, Rgb565::RED);
+        assert_eq!(flush(&mut display), (1, FRAME_BYTES));
+
+        display
+            .start_horizontal_scroll(0..8, 1, ScrollSpeed::Normal)
+            .unwrap();
+        display.stop_scroll().unwrap();
+        assert_eq!(flush(&mut display), (1, FRAME_BYTES));
+    }
+
+    #[test]
+    fn vertical_scroll_keeps_gddram() {
+        let mut display = display();
+        display.set_scroll_offset(5).unwrap();
+        pixel(&mut display, 10, 10, Rgb565::RED);
+        assert_eq!(flush(&mut display), (1, 2));
+    }
+
+    #[test]
+    fn turn_on_sends_whole_frame_after_reset() {
+        let mut display = display();
+        display.turn_off().unwrap();
+        display.turn_on().unwrap();
+        pixel(&mut display, 10, 10, Rgb565::RED);
+        assert_eq!(flush(&mut display), (1, 2));
+
+        display.assume_reset();
+        display.turn_on().unwrap();
+        assert_eq!(flush(&mut display), (1, FRAME_BYTES));
     }
 }
@@ -0,0 +1,444 @@
+//! Frame buffer shared by the buffered display drivers
+use crate::display::{
+    colour::ColourMode,
+    config::ByteOrder,
+    controller::Pixels,
+    dirty::{DirtyArea, DirtyTracker},
+    display::CHUNK_SIZE,
+    rotation::Orientation,
+    shadow::Shadow,
+};
+use core::{convert::Infallible, mem::size_of};
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    geometry::{Dimensions, OriginDimensions, Size},
+    primitives::Rectangle,
+    Pixel,
+};
+
+// Bytes sent to set up a GDDRAM window: column and row addresses, write RAM
+const WINDOW_COST: usize = 7;
+
+/// Pixels of a `WIDTH` x `HEIGHT` panel in the colour `C`, with the area changed since the last
+/// flush.
+///
+/// The drivers send the windows returned by `flush_windows` with the bytes of `window_data`.
+pub(crate) struct Frame<const WIDTH: usize, const HEIGHT: usize, S, C: ColourMode> {
+    // The rotation swaps the dimensions of the image
+    transposed: bool,
+    buffer: [[C::Bytes; WIDTH]; HEIGHT],
+    byte_order: ByteOrder,
+    dirty: DirtyTracker,
+    shadow: S,
+}
+
+impl<const WIDTH: usize, const HEIGHT: usize, S, C> Frame<WIDTH, HEIGHT, S, C>
+where
+    S: Shadow<C>,
+    C: ColourMode,
+{
+    /// Creates a black frame, marked to be sent whole by the next flush.
+    ///
+    /// The pixels are stored in `byte_order`, as they are sent to the controller.
+    pub(crate) fn new(shadow: S, byte_order: ByteOrder) -> Self {
+        let mut frame = Self {
+            transposed: false,
+            buffer: [[C::Bytes::default(); WIDTH]; HEIGHT],
+            byte_order,
+            dirty: DirtyTracker::default(),
+            shadow,
+        };
+        frame.mark_all_dirty();
+        frame
+    }
+
+    /// Replaces the copy of the GDDRAM content.
+    pub(crate) fn with_shadow<T: Shadow<C>>(self, shadow: T) -> Frame<WIDTH, HEIGHT, T, C> {
+        Frame {
+            transposed: self.transposed,
+            buffer: self.buffer,
+            byte_order: self.byte_order,
+            dirty: self.dirty,
+            shadow,
+        }
+    }
+
+    /// Returns the dimensions of the rotated image.
+    pub(crate) fn dimensions(&self) -> (usize, usize) {
+        if self.transposed {
+            (HEIGHT, WIDTH)
+        } else {
+            (WIDTH, HEIGHT)
+        }
+    }
+
+    /// Returns the windows to send to make the changed pixels visible, and resets the changed area.
+    ///
+    /// Only the bounding box of the pixels drawn since the last flush is returned, or the changed
+    /// spans within it when the GDDRAM content is known.
+    pub(crate) fn flush_windows(&mut self) -> FlushWindows {
+        let area = match self.dirty.take() {
+            Some(area) => area,
+            None => return FlushWindows::Done,
+        };
+
+        if self.shadow.pixels().is_some() {
+            // Compare the cost of the changed spans with sending the whole area
+            let pixel_size = size_of::<C::Bytes>();
+            let mut diff_cost = 0;
+            let mut row = area.top;
+            while let Some(window) = self.next_diff_window(area, row) {
+                diff_cost += window.pixel_count() * pixel_size + WINDOW_COST;
+                row = window.bottom + 1;
+            }
+            if diff_cost * 4 < (area.pixel_count() * pixel_size + WINDOW_COST) * 3 {
+                return FlushWindows::Diff {
+                    area,
+                    row: area.top,
+                };
+            }
+        }
+        FlushWindows::Area(area)
+    }
+
+    /// Returns the next window to send, `None` once all of them were returned.
+    pub(crate) fn next_window(&self, windows: &mut FlushWindows) -> Option<DirtyArea> {
+        match *windows {
+            FlushWindows::Done => None,
+            FlushWindows::Area(area) => {
+                *windows = FlushWindows::Done;
+                Some(area)
+            }
+            FlushWindows::Diff { area, row } => {
+                let window = self.next_diff_window(area, row);
+                *windows = match window {
+                    Some(window) => FlushWindows::Diff {
+                        area,
+                        row: window.bottom + 1,
+                    },
+                    None => FlushWindows::Done,
+                };
+                window
+            }
+        }
+    }
+
+    /// Returns the part of `area` within the image.
+    pub(crate) fn clip(&self, area: &Rectangle) -> Option<DirtyArea> {
+        let area = area.intersection(&self.bounding_box());
+        let bottom_right = area.bottom_right()?;
+        Some(DirtyArea::new(
+            area.top_left.x as usize,
+            area.top_left.y as usize,
+            bottom_right.x as usize,
+            bottom_right.y as usize,
+        ))
+    }
+
+    /// Returns the bytes of the pixels within `window`, in the order the GDDRAM expects them.
+    pub(crate) fn window_data(&self, window: DirtyArea) -> impl Iterator<Item = &[u8]> + '_ {
+        let (width, _) = self.dimensions();
+        let pixels = self.buffer.as_flattened();
+        // Full rows are contiguous in the frame buffer
+        let full_rows = window.left == 0 && window.right == width - 1;
+        let (chunks, rows) = if full_rows {
+            let bytes = C::as_bytes(&pixels[window.top * width..(window.bottom + 1) * width]);
+            (Some(bytes.chunks(CHUNK_SIZE)), None)
+        } else {
+            let rows = (window.top..=window.bottom).map(move |row| {
+                let start = row * width;
+                C::as_bytes(&pixels[start + window.left..=start + window.right])
+            });
+            (None, Some(rows))
+        };
+        chunks
+            .into_iter()
+            .flatten()
+            .chain(rows.into_iter().flatten())
+    }
+
+    /// Records the pixels of `window` as sent to the GDDRAM.
+    pub(crate) fn window_sent(&mut self, window: DirtyArea) {
+        let (width, height) = self.dimensions();
+        let pixels = self.buffer.as_flattened();
+        for row in window.top..=window.bottom {
+            let start = row * width;
+            self.shadow.update(
+                start + window.left,
+                &pixels[start + window.left..=start + window.right],
+            );
+        }
+        if window.pixel_count() == width * height {
+            // The copy matches the GDDRAM once the whole frame was sent
+            self.shadow.set_valid(true);
+        }
+    }
+
+    /// Returns the next window of changed pixels within `area`, starting from `row`.
+    ///
+    /// The changed span of each row is merged with the window of the previous rows while it costs
+    /// fewer bytes than setting up a new window.
+    fn next_diff_window(&self, area: DirtyArea, row: usize) -> Option<DirtyArea> {
+        let shadow = self.shadow.pixels()?;
+        let (width, _) = self.dimensions();
+        let pixels = self.buffer.as_flattened();
+
+        let mut window: Option<DirtyArea> = None;
+        for y in row..=area.bottom {
+            let start = y * width;
+            let new = &pixels[start + area.left..=start + area.right];
+            let old = &shadow[start + area.left..=start + area.right];
+            let span = new
+                .iter()
+                .zip(old)
+                .position(|(new, old)| new != old)
+                .zip(new.iter().zip(old).rposition(|(new, old)| new != old))
+                .map(|(first, last)| DirtyArea::new(area.left + first, y, area.left + last, y));
+
+            window = match (window, span) {
+                (None, None) => continue,
+                (None, Some(span)) => Some(span),
+                (Some(current), Some(span)) => {
+                    let merged = current.union(span);
+                    let separate = current.pixel_count() + span.pixel_count() + WINDOW_COST / 2;
+                    if merged.pixel_count() > separate {
+                        break;
+                    }
+                    Some(merged)
+                }
+                (Some(_), None) => break,
+            };
+        }
+        window
+    }
+
+    /// Returns the pixels of the rotated image, row by row.
+    #[cfg(test)]
+    pub(crate) fn pixels(&self) -> &[C::Bytes] {
+        self.buffer.as_flattened()
+    }
+
+    /// Marks the whole frame buffer to be sent by the next flush.
+    fn mark_all_dirty(&mut self) {
+        let (width, height) = self.dimensions();
+        self.dirty.mark(DirtyArea::new(0, 0, width - 1, height - 1));
+    }
+}
+
+impl<const WIDTH: usize, const HEIGHT: usize, S, C> Pixels for Frame<WIDTH, HEIGHT, S, C>
+where
+    S: Shadow<C>,
+    C: ColourMode,
+{
+    fn set_orientation(&mut self, orientation: Orientation) {
+        self.transposed = orientation.rotation.is_transposed();
+        self.invalidate_gddram();
+    }
+
+    /// The next flush sends the whole frame buffer.
+    fn invalidate_gddram(&mut self) {
+        self.shadow.set_valid(false);
+        // The changed area may hold the dimensions of the previous rotation
+        self.dirty.take();
+        self.mark_all_dirty();
+    }
+}
+
+/// Windows left to send by a flush.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum FlushWindows {
+    /// Nothing left to send
+    Done,
+    /// The whole changed area
+    Area(DirtyArea),
+    /// The changed spans of the area, from `row`
+    Diff { area: DirtyArea, row: usize },
+}
+
+impl<const WIDTH: usize, const HEIGHT: usize, S, C> DrawTarget for Frame<WIDTH, HEIGHT, S, C>
+where
+    S: Shadow<C>,
+    C: ColourMode,
+{
+    type Color = C;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        // The frame buffer holds the rotated image row by row, as the controller expects it
+        let (width, height) = self.dimensions();
+        let byte_order = self.byte_order;
+        let buffer = self.buffer.as_flattened_mut();
+        let mut dirty: Option<DirtyArea> = None;
+        pixels.into_iter().for_each(|Pixel(point, colour_pixel)| {
+            // ignore out-of-bounds drawing
+            if point.x < 0 || point.y < 0 || point.x >= width as i32 || point.y >= height as i32 {
+                return;
+            }
+            let (x, y) = (point.x as usize, point.y as usize);
+            buffer[x + y * width] = colour_pixel.to_bytes(byte_order);
+
+            let pixel = DirtyArea::pixel(x, y);
+            dirty = Some(dirty.map_or(pixel, |area| area.union(pixel)));
+        });
+
+        if let Some(area) = dirty {
+            self.dirty.mark(area);
+        }
+        Ok(())
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let drawable = match self.clip(area) {
+            Some(drawable) => drawable,
+            None => return Ok(()),
+        };
+
+        // The colours cover the whole area, skip the ones of the clipped pixels
+        let area_width = area.size.width as usize;
+        let skip_top = (drawable.top as i32 - area.top_left.y) as usize * area_width;
+        let skip_left = (drawable.left as i32 - area.top_left.x) as usize;
+        let skip_right = area_width - skip_left - (drawable.right - drawable.left + 1);
+
+        let (width, _) = self.dimensions();
+        let byte_order = self.byte_order;
+        let pixels = self.buffer.as_flattened_mut();
+        let mut colors = colors.into_iter();
+        if skip_top > 0 {
+            colors.nth(skip_top - 1);
+        }
+        for row in drawable.top..=drawable.bottom {
+            if skip_left > 0 {
+                colors.nth(skip_left - 1);
+            }
+            let start = row * width;
+            for (pixel, colour) in pixels[start + drawable.left..=start + drawable.right]
+                .iter_mut()
+                .zip(&mut colors)
+            {
+                *pixel = colour.to_bytes(byte_order);
+            }
+            if skip_right > 0 {
+                colors.nth(skip_right - 1);
+            }
+        }
+
+        self.dirty.mark(drawable);
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let area = match self.clip(area) {
+            Some(area) => area,
+            None => return Ok(()),
+        };
+
+        let colour = color.to_bytes(self.byte_order);
+        let (width, _) = self.dimensions();
+        let pixels = self.buffer.as_flattened_mut();
+        for row in area.top..=area.bottom {
+            let start = row * width;
+            pixels[start + area.left..=start + area.right].fill(colour);
+        }
+
+        self.dirty.mark(area);
+        Ok(())
+    }
+
+    fn clear(&mut self, fill: C) -> Result<(), Self::Error> {
+        let colour = fill.to_bytes(self.byte_order);
+        for row in self.buffer.iter_mut() {
+            row.fill(colour);
+        }
+        self.mark_all_dirty();
+        Ok(())
+    }
+}
+
+impl<const WIDTH: usize, const HEIGHT: usize, S, C> OriginDimensions for Frame<WIDTH, HEIGHT, S, C>
+where
+    S: Shadow<C>,
+    C: ColourMode,
+{
+    fn size(&self) -> Size {
+        let (width, height) = self.dimensions();
+        Size::new(width as u32, height as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::{rotation::Rotation, shadow::NoShadow};
+    use embedded_graphics::{geometry::Point, pixelcolor::Rgb565, primitives::PointsIter};
+
+    type TestFrame = Frame<16, 12, NoShadow, Rgb565>;
+
+    /// Returns distinct colours for the pixels of `area`.
+    fn image(area: &Rectangle) -> impl Iterator<Item = Rgb565> {
+        let count = area.size.width * area.size.height;
+        (0..count).map(|i| Rgb565::new((i % 32) as u8, (i / 32 % 64) as u8, 31 - (i % 31) as u8))
+    }
+
+    /// Checks that `fill_contiguous` draws the pixels of `draw_iter`, in the image rotated by
+    /// `rotation`.
+    fn assert_fill_matches_pixels(rotation: Rotation, area: Rectangle) {
+        let orientation = Orientation {
+            rotation,
+            ..Orientation::default()
+        };
+        let mut filled = TestFrame::new(NoShadow, ByteOrder::BigEndian);
+        let mut drawn = TestFrame::new(NoShadow, ByteOrder::BigEndian);
+        for frame in [&mut filled, &mut drawn] {
+            frame.set_orientation(orientation);
+            frame.dirty.take();
+        }
+
+        filled.fill_contiguous(&area, image(&area)).unwrap();
+        let pixels = area.points().zip(image(&area)).map(|(p, c)| Pixel(p, c));
+        drawn.draw_iter(pixels).unwrap();
+
+        assert!(filled.buffer == drawn.buffer, "pixels of {:?}", area);
+        assert_eq!(
+            filled.dirty.take(),
+            drawn.dirty.take(),
+            "area of {:?}",
+            area
+        );
+    }
+
+    #[test]
+    fn fill_contiguous_clips_each_edge() {
+        let size = Size::new(7, 5);
+        for rotation in [Rotation::Deg0, Rotation::Deg90] {
+            let fill = |x, y, size| {
+                assert_fill_matches_pixels(rotation, Rectangle::new(Point::new(x, y), size))
+            };
+            fill(-3, 4, size);
+            fill(4, -2, size);
+            fill(12, 4, size);
+            fill(4, 10, size);
+            fill(-2, -3, Size::new(20, 20));
+            fill(3, 3, size);
+        }
+    }
+
+    #[test]
+    fn fill_contiguous_ignores_area_outside() {
+        for rotation in [Rotation::Deg0, Rotation::Deg90] {
+            assert_fill_matches_pixels(
+                rotation,
+                Rectangle::new(Point::new(-8, 2), Size::new(8, 3)),
+            );
+            assert_fill_matches_pixels(
+                rotation,
+                Rectangle::new(Point::new(2, 16), Size::new(3, 3)),
+            );
+        }
+    }
+}
@@ -1,8 +1,21 @@
 //! SSD1351 Display Driver
-#![cfg_attr(not(test), no_std)]
-#![forbid(unsafe_code)]
-//#![doc(html_root_url = "https://docs.rs/ssd1351/0.2.0")]
-#![warn(missing_docs, rust_2018_idioms, unused_qualifications)]
 
+#[cfg(feature = "async")]
+pub mod async_display;
+pub mod colour;
 pub mod command;
+#[cfg(feature = "embedded-hal-02")]
+pub mod compat;
+pub mod config;
+mod controller;
+mod dirty;
+#[allow(clippy::module_inception)]
 pub mod display;
+mod frame;
+pub mod rotation;
+pub mod shadow;
+pub mod state;
+#[cfg(test)]
+mod testing;
+pub mod three_wire;
+pub mod unbuffered;
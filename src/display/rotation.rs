@@ -0,0 +1,152 @@
+//! Orientation of the image on the panel
+
+use crate::display::{
+    command::{Command, Remap},
+    config::Config,
+    display::GDDRAM_HEIGHT,
+};
+
+/// Clockwise rotation of the image on the panel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Rotation {
+    /// No rotation
+    #[default]
+    Deg0,
+    /// Rotate by 90 degrees
+    Deg90,
+    /// Rotate by 180 degrees
+    Deg180,
+    /// Rotate by 270 degrees
+    Deg270,
+}
+
+impl Rotation {
+    /// Returns true when the columns of the image are sent as rows to the panel.
+    pub(crate) fn is_transposed(self) -> bool {
+        matches!(self, Self::Deg90 | Self::Deg270)
+    }
+
+    /// Returns the address increment, column remap and COM scan direction of the re-map register.
+    ///
+    /// `horizontal` and `vertical` mirror the image along the axes of the rotated image.
+    fn scan(self, horizontal: bool, vertical: bool) -> Scan {
+        let (vertical_increment, column_remap, com_scan_reverse) = match self {
+            Self::Deg0 => (false, false, true),
+            Self::Deg90 => (true, true, true),
+            Self::Deg180 => (false, true, false),
+            Self::Deg270 => (true, false, false),
+        };
+
+        // The image columns follow the COM lines once transposed
+        let (flip_columns, flip_rows) = if self.is_transposed() {
+            (vertical, horizontal)
+        } else {
+            (horizontal, vertical)
+        };
+        Scan {
+            vertical_increment,
+            column_remap: column_remap ^ flip_columns,
+            com_scan_reverse: com_scan_reverse ^ flip_rows,
+        }
+    }
+}
+
+/// Settings of the re-map register driven by the orientation.
+#[derive(Clone, Copy)]
+struct Scan {
+    vertical_increment: bool,
+    column_remap: bool,
+    com_scan_reverse: bool,
+}
+
+/// Rotation, mirroring and vertical scroll of the image, shared by the display drivers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Orientation {
+    pub(crate) rotation: Rotation,
+    pub(crate) mirror: (bool, bool),
+    /// GDDRAM rows the image is scrolled by, with the start line
+    pub(crate) scroll: u8,
+}
+
+impl Orientation {
+    /// Returns the re-map setting of the configuration adjusted to the orientation.
+    pub(crate) fn remap(self, config: &Config) -> Remap {
+        let (horizontal, vertical) = self.mirror;
+        // The configuration holds the scan of the image without rotation
+        let base = Rotation::Deg0.scan(false, false);
+        let scan = self.rotation.scan(horizontal, vertical);
+        let remap = config.remap;
+        Remap {
+            vertical_increment: remap.vertical_increment
+                ^ base.vertical_increment
+                ^ scan.vertical_increment,
+            column_remap: remap.column_remap ^ base.column_remap ^ scan.column_remap,
+            com_scan_reverse: remap.com_scan_reverse
+                ^ base.com_scan_reverse
+                ^ scan.com_scan_reverse,
+            ..remap
+        }
+    }
+
+    /// Returns the start line showing the same GDDRAM rows once the COM scan direction changed,
+    /// moved by the scroll.
+    pub(crate) fn start_line(self, config: &Config) -> u8 {
+        let start_line = usize::from(config.start_line) + usize::from(self.scroll);
+        let height = usize::from(config.height);
+        let line = if self.remap(config).com_scan_reverse == config.remap.com_scan_reverse {
+            start_line
+        } else if config.remap.com_scan_reverse {
+            start_line + GDDRAM_HEIGHT - height
+        } else {
+            start_line + height
+        };
+        (line % GDDRAM_HEIGHT) as u8
+    }
+
+    /// Returns the dimensions of the rotated image of a `width` x `height` panel.
+    pub(crate) fn dimensions(self, width: usize, height: usize) -> (usize, usize) {
+        if self.rotation.is_transposed() {
+            (height, width)
+        } else {
+            (width, height)
+        }
+    }
+
+    /// Returns the commands initializing the display, with the re-map and start line adjusted.
+    pub(crate) fn init_sequence(self, config: &Config) -> impl Iterator<Item = Command> + '_ {
+        config.init_sequence().map(move |command| match command {
+            Command::SetRemap(_) => Command::SetRemap(self.remap(config)),
+            Command::DisplayStartLine(_) => Command::DisplayStartLine(self.start_line(config)),
+            command => command,
+        })
+    }
+
+    /// Returns the commands selecting a GDDRAM window, in image coordinates.
+    pub(crate) fn address_window(
+        self,
+        config: &Config,
+        left: usize,
+        top: usize,
+        right: usize,
+        bottom: usize,
+    ) -> [Command; 2] {
+        // The image columns are sent as rows once transposed
+        let (columns, rows) = if self.rotation.is_transposed() {
+            ((top, bottom), (left, right))
+        } else {
+            ((left, right), (top, bottom))
+        };
+        let (column_offset, row_offset) = (config.column_offset, config.row_offset);
+
+        [
+            Command::ColumnAddress {
+                start: column_offset.saturating_add(columns.0 as u8),
+                end: column_offset.saturating_add(columns.1 as u8),
+            },
+            Command::RowAddress {
+                start: row_offset.saturating_add(rows.0 as u8),
+                end: row_offset.saturating_add(rows.1 as u8),
+            },
+        ]
+    }
+}
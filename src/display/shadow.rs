@@ -0,0 +1,69 @@
+//! Copy of the GDDRAM content used to send only the changed pixels
+use crate::display::colour::ColourMode;
+use embedded_graphics::pixelcolor::Rgb565;
+
+/// Storage of the pixels last sent to the controller.
+///
+/// Implemented by `NoShadow`, the default, and by `FrameShadow` which enables the frame diff flush.
+pub trait Shadow<C: ColourMode = Rgb565> {
+    /// Returns the pixels last sent, or `None` when they are unknown.
+    fn pixels(&self) -> Option<&[C::Bytes]>;
+
+    /// Records the pixels sent to the controller, starting at the `offset` pixel of the image.
+    fn update(&mut self, offset: usize, pixels: &[C::Bytes]);
+
+    /// Marks the content of the copy as valid or unknown.
+    fn set_valid(&mut self, valid: bool);
+}
+
+/// Disables the frame diff flush.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NoShadow;
+
+impl<C: ColourMode> Shadow<C> for NoShadow {
+    fn pixels(&self) -> Option<&[C::Bytes]> {
+        None
+    }
+
+    fn update(&mut self, _offset: usize, _pixels: &[C::Bytes]) {}
+
+    fn set_valid(&mut self, _valid: bool) {}
+}
+
+/// Copy of the pixels of a `WIDTH` x `HEIGHT` panel, enables the frame diff flush.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FrameShadow<const WIDTH: usize, const HEIGHT: usize, C: ColourMode = Rgb565> {
+    pixels: [[C::Bytes; WIDTH]; HEIGHT],
+    valid: bool,
+}
+
+impl<const WIDTH: usize, const HEIGHT: usize, C: ColourMode> Default
+    for FrameShadow<WIDTH, HEIGHT, C>
+{
+    fn default() -> Self {
+        Self {
+            pixels: [[C::Bytes::default(); WIDTH]; HEIGHT],
+            valid: false,
+        }
+    }
+}
+
+impl<const WIDTH: usize, const HEIGHT: usize, C: ColourMode> Shadow<C>
+    for FrameShadow<WIDTH, HEIGHT, C>
+{
+    fn pixels(&self) -> Option<&[C::Bytes]> {
+        if self.valid {
+            Some(self.pixels.as_flattened())
+        } else {
+            None
+        }
+    }
+
+    fn update(&mut self, offset: usize, pixels: &[C::Bytes]) {
+        self.pixels.as_flattened_mut()[offset..offset + pixels.len()].copy_from_slice(pixels);
+    }
+
+    fn set_valid(&mut self, valid: bool) {
+        self.valid = valid;
+    }
+}
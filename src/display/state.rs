@@ -0,0 +1,39 @@
+//! Lifecycle of the display
+
+use crate::Error;
+
+/// Lifecycle state of the display.
+///
+/// ```text
+/// Uninit --reset--> Reset --turn_on--> On <--turn_on/turn_off--> Sleep
+/// ```
+///
+/// The pixels can only be sent to an initialized display, `On` or `Sleep`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum State {
+    /// The driver was created, the controller state is unknown
+    #[default]
+    Uninit,
+    /// The controller was reset and waits for the initialization sequence
+    Reset,
+    /// The controller is initialized and the panel is on
+    On,
+    /// The controller is initialized and the panel is off, the GDDRAM is kept
+    Sleep,
+}
+
+impl State {
+    /// Returns true once the initialization sequence was sent.
+    pub fn is_initialized(self) -> bool {
+        matches!(self, Self::On | Self::Sleep)
+    }
+
+    /// Fails with `Error::NotInitialized` before the initialization sequence was sent.
+    pub(crate) fn check_initialized(self) -> Result<(), Error> {
+        if self.is_initialized() {
+            Ok(())
+        } else {
+            Err(Error::NotInitialized)
+        }
+    }
+}
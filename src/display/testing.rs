@@ -0,0 +1,130 @@
+//! Simulated controller for the unit tests of the drivers
+
+use crate::display::display::{GDDRAM_HEIGHT, GDDRAM_WIDTH};
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+
+/// Display interface writing the `Rgb565` pixels to a simulated GDDRAM.
+///
+/// Only the commands addressing the GDDRAM are simulated: the column and row addresses, the
+/// address increment of the re-map register and the write RAM command.
+pub(crate) struct Gddram {
+    pub(crate) ram: [[[u8; 2]; GDDRAM_WIDTH]; GDDRAM_HEIGHT],
+    /// Windows written since the creation
+    pub(crate) windows: usize,
+    /// Bytes of pixels written since the creation
+    pub(crate) pixel_bytes: usize,
+    command: u8,
+    params: Vec<u8>,
+    columns: (usize, usize),
+    rows: (usize, usize),
+    vertical_increment: bool,
+    cursor: (usize, usize),
+    high_byte: Option<u8>,
+}
+
+impl Default for Gddram {
+    fn default() -> Self {
+        Self {
+            ram: [[[0; 2]; GDDRAM_WIDTH]; GDDRAM_HEIGHT],
+            windows: 0,
+            pixel_bytes: 0,
+            command: 0,
+            params: Vec::new(),
+            columns: (0, GDDRAM_WIDTH - 1),
+            rows: (0, GDDRAM_HEIGHT - 1),
+            vertical_increment: false,
+            cursor: (0, 0),
+            high_byte: None,
+        }
+    }
+}
+
+impl Gddram {
+    /// Returns the bytes of the pixel at `column` and `row`.
+    pub(crate) fn pixel(&self, column: usize, row: usize) -> [u8; 2] {
+        self.ram[row][column]
+    }
+
+    fn parameter(&mut self, byte: u8) {
+        self.params.push(byte);
+        match (self.command, &self.params[..]) {
+            (0x15, &[start, end]) => {
+                self.columns = (usize::from(start), usize::from(end));
+                self.cursor.0 = usize::from(start);
+            }
+            (0x75, &[start, end]) => {
+                self.rows = (usize::from(start), usize::from(end));
+                self.cursor.1 = usize::from(start);
+            }
+            (0xA0, &[remap]) => self.vertical_increment = remap & 0x01 != 0,
+            _ => {}
+        }
+    }
+
+    fn pixel_byte(&mut self, byte: u8) {
+        self.pixel_bytes += 1;
+        let high = match self.high_byte.take() {
+            Some(high) => high,
+            None => {
+                self.high_byte = Some(byte);
+                return;
+            }
+        };
+        let (column, row) = self.cursor;
+        self.ram[row][column] = [high, byte];
+
+        // The address wraps within the window
+        let next = |value: usize, (start, end): (usize, usize)| {
+            if value < end {
+                (value + 1, false)
+            } else {
+                (start, true)
+            }
+        };
+        self.cursor = if self.vertical_increment {
+            let (row, wrapped) = next(row, self.rows);
+            let column = if wrapped {
+                next(column, self.columns).0
+            } else {
+                column
+            };
+            (column, row)
+        } else {
+            let (column, wrapped) = next(column, self.columns);
+            let row = if wrapped { next(row, self.rows).0 } else { row };
+            (column, row)
+        };
+    }
+}
+
+impl WriteOnlyDataCommand for Gddram {
+    fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        match cmd {
+            DataFormat::U8(&[command]) => {
+                self.command = command;
+                self.params.clear();
+                if command == 0x5C {
+                    self.windows += 1;
+                    self.high_byte = None;
+                }
+                Ok(())
+            }
+            _ => Err(DisplayError::DataFormatNotImplemented),
+        }
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        let bytes = match buf {
+            DataFormat::U8(bytes) => bytes,
+            _ => return Err(DisplayError::DataFormatNotImplemented),
+        };
+        for &byte in bytes {
+            if self.command == 0x5C {
+                self.pixel_byte(byte);
+            } else {
+                self.parameter(byte);
+            }
+        }
+        Ok(())
+    }
+}
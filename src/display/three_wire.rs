@@ -0,0 +1,197 @@
+//! 3-wire serial interface, sending the data/command bit before each byte
+//!
+//! In the 3-wire mode the controller reads 9-bit words: the D/C bit, low for commands and high for
+//! data, followed by the byte, most significant bit first. The words are packed into the bytes
+//! written to the SPI device, eight words fitting in nine bytes.
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+use embedded_hal::spi::SpiDevice;
+
+// Bytes packed per SPI write, a multiple of 8 keeps the writes aligned on the 9-bit words
+const CHUNK_BYTES: usize = 64;
+const CHUNK_LEN: usize = CHUNK_BYTES / 8 * 9;
+
+/// 3-wire SPI display interface, without data/command pin.
+///
+/// The last byte of each write is padded with zeros when it holds a partial word. The controller
+/// drops the padding bits when the SPI device releases chip select at the end of the write.
+#[derive(Debug)]
+pub struct ThreeWireSpiInterface<SPI> {
+    spi: SPI,
+}
+
+impl<SPI: SpiDevice> ThreeWireSpiInterface<SPI> {
+    /// Creates the interface from the SPI device.
+    pub fn new(spi: SPI) -> Self {
+        Self { spi }
+    }
+
+    /// Returns the SPI device.
+    pub fn release(self) -> SPI {
+        self.spi
+    }
+
+    fn send(&mut self, dc: bool, bytes: DataFormat<'_>) -> Result<(), DisplayError> {
+        let bytes = match bytes {
+            DataFormat::U8(bytes) => bytes,
+            _ => return Err(DisplayError::DataFormatNotImplemented),
+        };
+
+        let mut words = [0; CHUNK_LEN];
+        for chunk in bytes.chunks(CHUNK_BYTES) {
+            let len = pack(dc, chunk, &mut words);
+            self.spi
+                .write(&words[..len])
+                .map_err(|_| DisplayError::BusWriteError)?;
+        }
+        Ok(())
+    }
+}
+
+impl<SPI: SpiDevice> WriteOnlyDataCommand for ThreeWireSpiInterface<SPI> {
+    fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.send(false, cmd)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.send(true, buf)
+    }
+}
+
+/// Packs `bytes` into 9-bit words starting with the `dc` bit, returns the number of bytes written.
+///
+/// `words` must hold `bytes.len() * 9 / 8` bytes, rounded up.
+fn pack(dc: bool, bytes: &[u8], words: &mut [u8]) -> usize {
+    // The bits not written yet are the lowest `count` bits of `bits`
+    let mut bits = 0u32;
+    let mut count = 0;
+    let mut len = 0;
+    for &byte in bytes {
+        bits = (bits << 9) | (u32::from(dc) << 8) | u32::from(byte);
+        count += 9;
+        while count >= 8 {
+            count -= 8;
+            words[len] = (bits >> count) as u8;
+            len += 1;
+        }
+    }
+    if count > 0 {
+        words[len] = (bits << (8 - count)) as u8;
+        len += 1;
+    }
+    len
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::Infallible;
+    use embedded_hal::spi::{ErrorType, Operation};
+
+    // Records the bytes of each write
+    #[derive(Default)]
+    struct Recorder {
+        writes: Vec<Vec<u8>>,
+    }
+
+    impl ErrorType for Recorder {
+        type Error = Infallible;
+    }
+
+    impl SpiDevice for Recorder {
+        fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Infallible> {
+            for operation in operations {
+                if let Operation::Write(bytes) = operation {
+                    self.writes.push(bytes.to_vec());
+                }
+            }
+            Ok(())
+        }
+    }
+
+    // Packs the words bit by bit
+    fn pack_bits(dc: bool, bytes: &[u8]) -> Vec<u8> {
+        let mut bits = Vec::new();
+        for &byte in bytes {
+            bits.push(dc);
+            bits.extend((0..8).rev().map(|bit| byte & (1 << bit) != 0));
+        }
+        bits.chunks(8)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .enumerate()
+                    .fold(0, |word, (i, &bit)| word | (u8::from(bit) << (7 - i)))
+            })
+            .collect()
+    }
+
+    fn packed(dc: bool, bytes: &[u8]) -> Vec<u8> {
+        let mut words = [0; CHUNK_LEN + 1];
+        let len = pack(dc, bytes, &mut words);
+        words[..len].to_vec()
+    }
+
+    #[test]
+    fn command_byte() {
+        // 0 1010 1111, padded with zeros
+        assert_eq!(packed(false, &[0xAF]), [0x57, 0x80]);
+    }
+
+    #[test]
+    fn data_byte() {
+        // 1 0000 0000, padded with zeros
+        assert_eq!(packed(true, &[0x00]), [0x80, 0x00]);
+        assert_eq!(packed(true, &[0xFF]), [0xFF, 0x80]);
+    }
+
+    #[test]
+    fn eight_bytes_fill_nine_bytes() {
+        assert_eq!(packed(false, &[0; 8]), [0; 9]);
+        assert_eq!(
+            packed(true, &[0; 8]),
+            [0x80, 0x40, 0x20, 0x10, 0x08, 0x04, 0x02, 0x01, 0x00]
+        );
+    }
+
+    #[test]
+    fn matches_bit_by_bit_packing() {
+        let bytes: Vec<u8> = (0..CHUNK_BYTES as u32)
+            .map(|i| (i * 37 + 11) as u8)
+            .collect();
+        for len in 0..=bytes.len() {
+            for &dc in &[false, true] {
+                assert_eq!(packed(dc, &bytes[..len]), pack_bits(dc, &bytes[..len]));
+            }
+        }
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(packed(true, &[]), []);
+    }
+
+    #[test]
+    fn writes_are_aligned_on_words() {
+        let mut interface = ThreeWireSpiInterface::new(Recorder::default());
+        interface.send_commands(DataFormat::U8(&[0x5C])).unwrap();
+        interface
+            .send_data(DataFormat::U8(&[0xFF; CHUNK_BYTES + 1]))
+            .unwrap();
+
+        let writes = interface.release().writes;
+        let lengths: Vec<usize> = writes.iter().map(Vec::len).collect();
+        assert_eq!(lengths, [2, CHUNK_LEN, 2]);
+        assert_eq!(writes[0], [0x2E, 0x00]);
+        assert_eq!(writes[2], [0xFF, 0x80]);
+    }
+
+    #[test]
+    fn rejects_other_formats() {
+        let mut interface = ThreeWireSpiInterface::new(Recorder::default());
+        assert!(matches!(
+            interface.send_data(DataFormat::U16(&[0xFFFF])),
+            Err(DisplayError::DataFormatNotImplemented)
+        ));
+        assert_eq!(interface.release().writes, vec![] as Vec<Vec<u8>>);
+    }
+}
@@ -0,0 +1,349 @@
+//! Display driver writing the pixels directly to the controller
+use crate::display::{
+    colour::ColourMode,
+    command::Command,
+    config::Config,
+    controller::Controller,
+    dirty::DirtyArea,
+    display::{hard_reset, send_commands, send_pixels, GDDRAM_WIDTH},
+    rotation::Rotation,
+    state::State,
+};
+use crate::Error;
+use display_interface::WriteOnlyDataCommand;
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    geometry::{Dimensions, OriginDimensions, Point, Size},
+    pixelcolor::Rgb565,
+    primitives::Rectangle,
+    Pixel,
+};
+use embedded_hal::{delay::DelayNs, digital::OutputPin};
+
+/// Represents the SSD1351 Display without a frame buffer.
+///
+/// Every drawing operation sets the GDDRAM window and writes its pixels to the controller, so the
+/// driver only needs a few hundred bytes of RAM instead of the 32 KiB frame buffer of `Ssd1351`.
+/// Filled rectangles and images are sent as one window, single pixels cost a window each unless
+/// they follow each other on a row.
+///
+/// ```ignore
+/// let mut disp: Ssd1351Unbuffered<_, 128, 96> =
+///     Ssd1351Unbuffered::with_config(spii, Config::adafruit_128x96());
+/// ```
+pub struct Ssd1351Unbuffered<DI, const WIDTH: usize = 128, const HEIGHT: usize = 128> {
+    display: DI,
+    controller: Controller<WIDTH, HEIGHT, ()>,
+}
+
+impl<DI: WriteOnlyDataCommand> Ssd1351Unbuffered<DI> {
+    /// Creates the 128x128 SSD1351 Display.
+    ///
+    /// Make sure to reset and initialize the display before use!
+    pub fn new(display: DI) -> Self {
+        Self::with_config(display, Config::default())
+    }
+}
+
+impl<DI, const WIDTH: usize, const HEIGHT: usize> Ssd1351Unbuffered<DI, WIDTH, HEIGHT>
+where
+    DI: WriteOnlyDataCommand,
+{
+    /// Creates the SSD1351 Display with the settings applied by `turn_on`.
+    ///
+    /// Use one of the `Config` presets to match the module. Its geometry must be `WIDTH` x
+    /// `HEIGHT`, `turn_on` fails with `Error::InvalidArgument` otherwise. The colour depth is
+    /// replaced by the 65k colour mode.
+    ///
+    /// The geometry is not inferred from the configuration: name the type of the display, the
+    /// defaults of the type parameters only apply to an annotation.
+    ///
+    /// ```ignore
+    /// let mut disp: Ssd1351Unbuffered<_> = Ssd1351Unbuffered::with_config(spii, Config::adafruit_128x128());
+    /// ```
+    ///
+    /// Make sure to reset and initialize the display before use!
+    pub fn with_config(display: DI, config: Config) -> Self {
+        Self {
+            display,
+            controller: Controller::new::<Rgb565>(config, ()),
+        }
+    }
+
+    /// Resets the display.
+    pub fn reset<RST, DELAY>(
+        &mut self,
+        rst: &mut RST,
+        delay: &mut DELAY,
+    ) -> Result<(), Error<RST::Error>>
+    where
+        RST: OutputPin,
+        DELAY: DelayNs,
+    {
+        hard_reset(rst, delay)?;
+        self.controller.reset();
+        Ok(())
+    }
+
+    /// Marks the display as reset, for boards without a reset pin.
+    ///
+    /// The controller resets itself at power on.
+    pub fn assume_reset(&mut self) {
+        self.controller.reset();
+    }
+
+    /// Initializes the display with the settings of its configuration.
+    ///
+    /// The display must be reset first. A display turned off is only turned back on, the GDDRAM
+    /// content is kept.
+    pub fn turn_on(&mut self) -> Result<(), Error> {
+        send_commands(&mut self.display, self.controller.turn_on()?)?;
+        self.controller.turned_on();
+        Ok(())
+    }
+
+    /// Returns the lifecycle state of the display.
+    pub fn state(&self) -> State {
+        self.controller.state
+    }
+
+    /// Rotates the image on the panel.
+    ///
+    /// The pixels already on the panel are rotated too, redraw the image afterwards.
+    pub fn set_rotation(&mut self, rotation: Rotation) -> Result<(), Error> {
+        send_commands(&mut self.display, self.controller.set_rotation(rotation))
+    }
+
+    /// Mirrors the image horizontally and/or vertically, after the rotation.
+    pub fn set_mirror(&mut self, horizontal: bool, vertical: bool) -> Result<(), Error> {
+        send_commands(
+            &mut self.display,
+            self.controller.set_mirror(horizontal, vertical),
+        )
+    }
+
+    /// Returns the rotation of the image.
+    pub fn rotation(&self) -> Rotation {
+        self.controller.rotation()
+    }
+
+    /// Sets the brightness, see `display::Ssd1351::set_brightness`.
+    pub fn set_brightness(&mut self, level: u8) -> Result<(), Error> {
+        let command = self.controller.set_brightness(level)?;
+        self.send_command(command)
+    }
+
+    /// Sets the contrast current of the red, green and blue channels, see
+    /// `display::Ssd1351::set_channel_contrast`.
+    pub fn set_channel_contrast(&mut self, red: u8, green: u8, blue: u8) -> Result<(), Error> {
+        let command = self.controller.set_channel_contrast(red, green, blue)?;
+        self.send_command(command)
+    }
+
+    /// Turns off the display, the GDDRAM content is kept.
+    pub fn turn_off(&mut self) -> Result<(), Error> {
+        let command = self.controller.turn_off()?;
+        self.send_command(command)?;
+        self.controller.turned_off();
+        Ok(())
+    }
+
+    /// Allows to send custom commands to the display.
+    pub fn send_command(&mut self, command: Command) -> Result<(), Error> {
+        command.send(&mut self.display)
+    }
+
+    /// Selects the GDDRAM window written by the next pixels, in image coordinates.
+    fn start_window(&mut self, window: DirtyArea) -> Result<(), Error> {
+        send_commands(&mut self.display, self.controller.address_window(window)?)
+    }
+
+    /// Returns the part of `area` within the display.
+    fn clip(&self, area: &Rectangle) -> Option<DirtyArea> {
+        let area = area.intersection(&self.bounding_box());
+        let bottom_right = area.bottom_right()?;
+        Some(DirtyArea::new(
+            area.top_left.x as usize,
+            area.top_left.y as usize,
+            bottom_right.x as usize,
+            bottom_right.y as usize,
+        ))
+    }
+}
+
+impl<DI, const WIDTH: usize, const HEIGHT: usize> DrawTarget
+    for Ssd1351Unbuffered<DI, WIDTH, HEIGHT>
+where
+    DI: WriteOnlyDataCommand,
+{
+    type Color = Rgb565;
+    type Error = Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let (width, height) = self.controller.dimensions();
+        let format = self.controller.pixel_format()?;
+        let byte_order = self.controller.config.byte_order;
+        // Position written by the next pixel of the current window
+        let mut next: Option<Point> = None;
+        for Pixel(point, colour_pixel) in pixels {
+            // ignore out-of-bounds drawing
+            if point.x < 0 || point.y < 0 || point.x >= width as i32 || point.y >= height as i32 {
+                continue;
+            }
+            // The window runs to the end of the row, following pixels need no new window
+            if next != Some(point) {
+                let (x, y) = (point.x as usize, point.y as usize);
+                self.start_window(DirtyArea::new(x, y, width - 1, y))?;
+            }
+            let colour = colour_pixel.to_bytes(byte_order);
+            send_pixels(&mut self.display, format, &colour)?;
+
+            next = if point.x + 1 < width as i32 {
+                Some(point + Point::new(1, 0))
+            } else {
+                None
+            };
+        }
+        Ok(())
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let window = match self.clip(area) {
+            Some(window) => window,
+            None => return Ok(()),
+        };
+
+        // The colours cover the whole area, skip the ones of the clipped pixels
+        let area_width = area.size.width as usize;
+        let skip_top = (window.top as i32 - area.top_left.y) as usize * area_width;
+        let skip_left = (window.left as i32 - area.top_left.x) as usize;
+        let skip_right = area_width - skip_left - (window.right - window.left + 1);
+
+        let format = self.controller.pixel_format()?;
+        let byte_order = self.controller.config.byte_order;
+        self.start_window(window)?;
+        let mut colors = colors.into_iter();
+        if skip_top > 0 {
+            colors.nth(skip_top - 1);
+        }
+        let mut row = [[0; 2]; GDDRAM_WIDTH];
+        let row = &mut row[..=window.right - window.left];
+        for _ in window.top..=window.bottom {
+            if skip_left > 0 {
+                colors.nth(skip_left - 1);
+            }
+            for (pixel, colour) in row.iter_mut().zip(&mut colors) {
+                *pixel = colour.to_bytes(byte_order);
+            }
+            send_pixels(&mut self.display, format, row.as_flattened())?;
+            if skip_right > 0 {
+                colors.nth(skip_right - 1);
+            }
+        }
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let window = match self.clip(area) {
+            Some(window) => window,
+            None => return Ok(()),
+        };
+
+        let format = self.controller.pixel_format()?;
+        self.start_window(window)?;
+        let row = [color.to_bytes(self.controller.config.byte_order); GDDRAM_WIDTH];
+        let mut remaining = window.pixel_count();
+        while remaining > 0 {
+            let count = remaining.min(GDDRAM_WIDTH);
+            send_pixels(&mut self.display, format, row[..count].as_flattened())?;
+            remaining -= count;
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self, fill: Rgb565) -> Result<(), Self::Error> {
+        self.fill_solid(&self.bounding_box(), fill)
+    }
+}
+
+impl<DI, const WIDTH: usize, const HEIGHT: usize> OriginDimensions
+    for Ssd1351Unbuffered<DI, WIDTH, HEIGHT>
+where
+    DI: WriteOnlyDataCommand,
+{
+    fn size(&self) -> Size {
+        let (width, height) = self.controller.dimensions();
+        Size::new(width as u32, height as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::testing::Gddram;
+    use embedded_graphics::primitives::PointsIter;
+
+    fn display(rotation: Rotation) -> Ssd1351Unbuffered<Gddram, 16, 12> {
+        let config = Config::default().geometry(16, 12);
+        let mut display = Ssd1351Unbuffered::with_config(Gddram::default(), config);
+        display.assume_reset();
+        display.turn_on().unwrap();
+        display.set_rotation(rotation).unwrap();
+        display
+    }
+
+    /// Returns distinct colours for the pixels of `area`.
+    fn image(area: &Rectangle) -> impl Iterator<Item = Rgb565> {
+        let count = area.size.width * area.size.height;
+        (0..count).map(|i| Rgb565::new((i % 32) as u8, (i / 32 % 64) as u8, 31 - (i % 31) as u8))
+    }
+
+    /// Checks that `fill_contiguous` writes the GDDRAM pixels of `draw_iter`.
+    fn assert_fill_matches_pixels(rotation: Rotation, area: Rectangle) {
+        let mut filled = display(rotation);
+        let mut drawn = display(rotation);
+
+        filled.fill_contiguous(&area, image(&area)).unwrap();
+        let pixels = area.points().zip(image(&area)).map(|(p, c)| Pixel(p, c));
+        drawn.draw_iter(pixels).unwrap();
+
+        assert!(
+            filled.display.ram == drawn.display.ram,
+            "pixels of {:?}",
+            area
+        );
+    }
+
+    #[test]
+    fn fill_contiguous_clips_each_edge() {
+        let size = Size::new(7, 5);
+        for rotation in [Rotation::Deg0, Rotation::Deg90] {
+            let fill = |x, y, size| {
+                assert_fill_matches_pixels(rotation, Rectangle::new(Point::new(x, y), size))
+            };
+            fill(-3, 4, size);
+            fill(4, -2, size);
+            fill(12, 4, size);
+            fill(4, 10, size);
+            fill(-2, -3, Size::new(20, 20));
+            fill(3, 3, size);
+        }
+    }
+
+    #[test]
+    fn fill_contiguous_ignores_area_outside() {
+        for rotation in [Rotation::Deg0, Rotation::Deg90] {
+            let mut display = display(rotation);
+            let windows = display.display.windows;
+            let area = Rectangle::new(Point::new(-8, 2), Size::new(8, 3));
+            display.fill_contiguous(&area, image(&area)).unwrap();
+            assert_eq!(display.display.windows, windows);
+        }
+    }
+}
@@ -0,0 +1,120 @@
+//! Errors returned by the display drivers
+
+use core::{convert::Infallible, fmt};
+use display_interface::DisplayError;
+use embedded_hal::{digital, spi};
+
+/// Error returned by the display drivers.
+///
+/// `PinE` and `SpiE` are the errors of the HAL pins and SPI device used by the driver, kept as the
+/// source of the error. The drivers of the `display` module send the bytes through a display
+/// interface, their errors are `Error` and only `reset` returns the error of its pin.
+///
+/// The delays of embedded-hal 1.0 cannot fail, there is no delay error.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum Error<PinE = Infallible, SpiE = Infallible> {
+    /// The bytes could not be sent to the controller
+    Bus(BusError<SpiE>),
+    /// A reset or data/command pin could not be set
+    Pin(PinE),
+    /// A command argument is outside the range accepted by the controller
+    InvalidArgument,
+    /// The display must be reset and turned on first
+    NotInitialized,
+}
+
+/// Failure of the bus connecting the controller.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum BusError<SpiE = Infallible> {
+    /// Error of the display interface
+    Interface(DisplayError),
+    /// Error of the SPI device
+    Spi(SpiE),
+}
+
+#[cfg(feature = "std")]
+impl Error {
+    /// Returns the error in the error type of a driver using HAL pins and SPI devices.
+    pub(crate) fn with_hal<PinE, SpiE>(self) -> Error<PinE, SpiE> {
+        match self {
+            Self::Bus(BusError::Interface(error)) => Error::Bus(BusError::Interface(error)),
+            Self::Bus(BusError::Spi(never)) | Self::Pin(never) => match never {},
+            Self::InvalidArgument => Error::InvalidArgument,
+            Self::NotInitialized => Error::NotInitialized,
+        }
+    }
+}
+
+impl<PinE, SpiE> From<DisplayError> for Error<PinE, SpiE> {
+    fn from(error: DisplayError) -> Self {
+        Self::Bus(BusError::Interface(error))
+    }
+}
+
+impl<PinE, SpiE> fmt::Display for Error<PinE, SpiE>
+where
+    PinE: digital::Error,
+    SpiE: spi::Error,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bus(BusError::Interface(error)) => {
+                write!(f, "display interface error: {:?}", error)
+            }
+            Self::Bus(BusError::Spi(error)) => write!(f, "SPI error: {}", error.kind()),
+            Self::Pin(error) => write!(f, "pin error: {}", error.kind()),
+            Self::InvalidArgument => f.write_str("command argument out of range"),
+            Self::NotInitialized => f.write_str("display not initialized"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<PinE, SpiE> std::error::Error for Error<PinE, SpiE>
+where
+    PinE: digital::Error + std::error::Error + 'static,
+    SpiE: spi::Error + std::error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Bus(BusError::Spi(error)) => Some(error),
+            Self::Pin(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+
+    #[derive(Debug)]
+    struct SpiFailure;
+
+    impl fmt::Display for SpiFailure {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("transfer failed")
+        }
+    }
+
+    impl std::error::Error for SpiFailure {}
+
+    impl spi::Error for SpiFailure {
+        fn kind(&self) -> spi::ErrorKind {
+            spi::ErrorKind::Overrun
+        }
+    }
+
+    #[test]
+    fn source_is_the_hal_error() {
+        let error: Error<Infallible, SpiFailure> = Error::Bus(BusError::Spi(SpiFailure));
+        assert_eq!(error.source().unwrap().to_string(), "transfer failed");
+        assert!(error.to_string().starts_with("SPI error: "));
+        assert!(Error::<Infallible, SpiFailure>::NotInitialized
+            .source()
+            .is_none());
+    }
+}
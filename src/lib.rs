@@ -1,8 +1,16 @@
 //! SSD1351 Display Driver
+//!
+//! The `display` module builds without the standard library. The `simple_display` module needs
+//! the `std` feature, and its Raspberry Pi constructor the `rppal` feature, enabled by default.
+#![cfg_attr(not(any(test, feature = "std")), no_std)]
 #![forbid(unsafe_code)]
 //#![doc(html_root_url = "https://docs.rs/ssd1351/0.2.0")]
 #![warn(missing_docs, rust_2018_idioms, unused_qualifications)]
 
 
 pub mod display;
+pub mod error;
+#[cfg(feature = "std")]
 pub mod simple_display;
+
+pub use error::Error;
@@ -1,10 +1,19 @@
 //! main display module
-use rppal::{
-    gpio::{Gpio, OutputPin},
-    spi::{Bus, Mode, SlaveSelect, Spi},
+use crate::Error;
+use embedded_graphics::{
+    draw_target::DrawTarget,
+    geometry::{Dimensions, OriginDimensions, Size},
+    pixelcolor::{
+        raw::{RawData, RawU16},
+        Rgb565,
+    },
+    primitives::Rectangle,
+    Pixel,
 };
+use embedded_hal::{digital::OutputPin, spi::SpiDevice};
 use std::{thread::sleep, time::Duration};
 
+use crate::display::config::Config;
 use crate::simple_display::command::Command;
 
 const DISPLAY_WIDTH: usize = 128;
@@ -13,119 +22,219 @@ const BUFFER_SIZE: usize = DISPLAY_WIDTH * DISPLAY_HEIGHT * 2;
 
 /// Represents the SSD1351 Display.
 ///
-/// Use this struct to initialize the driver.
-pub struct Ssd1351 {
-    spi: Spi,
-    rc: OutputPin,
-    rst: OutputPin,
+/// Use this struct to initialize the driver. It works with any embedded-hal SPI device and output
+/// pins, such as the spidev and gpio-cdev ones of linux-embedded-hal:
+///
+/// ```ignore
+/// let spi = SpidevDevice::open("/dev/spidev0.0")?;
+/// let mut disp = Ssd1351::new(spi, dc, rst);
+/// ```
+///
+/// `from_rppal` creates it from the rppal SPI bus and pins of a Raspberry Pi.
+///
+/// The buffer is filled with `update_buffer`, from a Cairo surface for instance, or through
+/// embedded-graphics as the display is a `DrawTarget`. Both can be mixed before a `flush`.
+pub struct Ssd1351<SPI, DC, RST> {
+    spi: SPI,
+    rc: DC,
+    rst: RST,
+    config: Config,
     buffer: [u8; BUFFER_SIZE],
 }
 
-impl Ssd1351 {
+#[cfg(feature = "rppal")]
+impl Ssd1351<rppal::spi::SimpleHalSpiDevice, rppal::gpio::OutputPin, rppal::gpio::OutputPin> {
+    /// Creates the SSD1351 Display from the rppal SPI bus and pins.
+    ///
+    /// Make sure to reset and initialize the display before use!
+    pub fn from_rppal(
+        spi: rppal::spi::Spi,
+        rc: rppal::gpio::OutputPin,
+        rst: rppal::gpio::OutputPin,
+    ) -> Self {
+        Self::new(rppal::spi::SimpleHalSpiDevice::new(spi), rc, rst)
+    }
+}
+
+impl<SPI, DC, RST> Ssd1351<SPI, DC, RST>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+    RST: OutputPin,
+{
     /// Creates the SSD1351 Display.
     ///
     /// Make sure to reset and initialize the display before use!
-    pub fn new(spi: Spi, rc: OutputPin, rst: OutputPin) -> Self {
+    pub fn new(spi: SPI, rc: DC, rst: RST) -> Self {
+        Self::with_config(spi, rc, rst, Config::default())
+    }
+
+    /// Creates the SSD1351 Display with the panel geometry and the settings applied by `turn_on`.
+    ///
+    /// Make sure to reset and initialize the display before use!
+    pub fn with_config(spi: SPI, rc: DC, rst: RST, config: Config) -> Self {
         Self {
-            spi: spi,
-            rc: rc,
-            rst: rst,
+            spi,
+            rc,
+            rst,
+            config,
             buffer: [0; BUFFER_SIZE],
         }
     }
 
     /// Resets the display.
-    pub fn reset(&mut self) -> Result<(), rppal::gpio::Error> {
+    pub fn reset(&mut self) -> Result<(), Error<RST::Error>> {
         let sleep_duration = Duration::from_millis(100);
-        self.rst.set_low();
+        self.rst.set_low().map_err(Error::Pin)?;
         sleep(sleep_duration);
 
-        self.rst.set_high();
+        self.rst.set_high().map_err(Error::Pin)?;
         sleep(sleep_duration);
 
         Ok(())
     }
 
     /// Copy an array of bytes to the buffer.
+    ///
+    /// The bytes hold native endian 16-bit colours, they are stored in the byte order of the
+    /// configuration.
     pub fn update_buffer(&mut self, bytes: &[u8]) {
-        let coef_big;
-        let coef_little;
-        if cfg!(target_endian = "big") {
-            coef_little = 0;
-            coef_big = 1;
-        } else {
-            coef_little = 1;
-            coef_big = 0;
-        }
-
-        for i in (0..BUFFER_SIZE - 1).step_by(2) {
-            self.buffer[i] = bytes[i + coef_little];
-            self.buffer[i + 1] = bytes[i + coef_big];
+        let byte_order = self.config.byte_order;
+        let len = self.buffer_len();
+        for (pixel, colour) in self.buffer[..len]
+            .chunks_exact_mut(2)
+            .zip(bytes.chunks_exact(2))
+        {
+            let raw = u16::from_ne_bytes([colour[0], colour[1]]);
+            pixel.copy_from_slice(&byte_order.bytes(raw));
         }
     }
 
         /// Allows to send custom commands to the display.
-        pub fn send_command(&mut self, command: Command) -> Result<(), rppal::spi::Error> {
+        pub fn send_command(&mut self, command: Command) -> Result<(), Error<DC::Error, SPI::Error>> {
             command.send_command(&mut self.rc, &mut self.spi)?;
             Ok(())
         }
     
         /// Allows to send the date to the display.
-        pub fn send_data(&mut self, data: &[u8]) -> Result<(), rppal::spi::Error> {
-            Command::send_data(&mut self.spi, data)?;
-            Ok(())
+        pub fn send_data(&mut self, data: &[u8]) -> Result<(), Error<DC::Error, SPI::Error>> {
+            Command::send_data(&mut self.spi, data)
         }
 
     /// Flushes the display, and makes the output visible on the screen.
-    pub fn flush(&mut self) -> Result<(), rppal::spi::Error> {
-        self.send_command(Command::ColumnAddress)?;
-        self.send_command(Command::RowAddress)?;
-        self.send_command(Command::DisplayStartLine)?;
+    pub fn flush(&mut self) -> Result<(), Error<DC::Error, SPI::Error>> {
+        self.send_command(self.config.column_address())?;
+        self.send_command(self.config.row_address())?;
+        self.send_command(Command::DisplayStartLine(self.config.start_line))?;
         self.send_command(Command::WriteRam)?;
 
-             let buffer = self.buffer;
-        self.send_data(&buffer)?;
+        let buffer = self.buffer;
+        self.send_data(&buffer[..self.buffer_len()])?;
         Ok(())
     }
 
-        /// Initializes the display.
-        pub fn turn_on(&mut self) -> Result<(), rppal::spi::Error> {
-            self.send_command(Command::Unlock0x12)?;
-            self.send_command(Command::Unlock0xB1)?;
-            self.send_command(Command::DisplayOff)?;
-            self.send_command(Command::ClockDivider)?;
-            self.send_command(Command::Muxratio)?;
-            self.send_command(Command::ColumnAddress)?;
-            self.send_command(Command::RowAddress)?;
-            self.send_command(Command::SegmentRemapping)?;
-            self.send_command(Command::DisplayStartLine)?;
-            self.send_command(Command::DisplayOffset)?;
-            self.send_command(Command::SetGpio)?;
-            self.send_command(Command::SelectInternalDiodeDrop)?;
-            self.send_command(Command::Precharge)?;
-            self.send_command(Command::SetSegmentLowVoltage)?;
-            self.send_command(Command::SetVcomHVoltage)?;
-            //self.send_command(Command::Invert)?;
-            self.send_command(Command::ContrastMaster)?;
-            self.send_command(Command::Precharge2)?;
-            self.send_command(Command::Contrast)?;
-            self.send_command(Command::DisplayOn)?;
-            self.send_command(Command::NomalDisplay)?;
-            self.send_command(Command::DisplayOffset)?;
+    /// Returns the number of bytes of the buffer used by the panel.
+    fn buffer_len(&self) -> usize {
+        self.config.pixel_count().min(DISPLAY_WIDTH * DISPLAY_HEIGHT) * 2
+    }
+
+        /// Initializes the display with the settings of its configuration.
+        pub fn turn_on(&mut self) -> Result<(), Error<DC::Error, SPI::Error>> {
+            for command in self.config.init_sequence() {
+                self.send_command(command)?;
+            }
             Ok(())
         }
-    
+
         /// Turns off the display.
-        pub fn turn_off(&mut self) -> Result<(), rppal::spi::Error> {
+        pub fn turn_off(&mut self) -> Result<(), Error<DC::Error, SPI::Error>> {
             self.send_command(Command::DisplayOff)?;
             Ok(())
         }
 
-    /// Clear the display
-    pub fn clear(&mut self) -> Result<(), rppal::gpio::Error> {
-        for i in 0..128 * 128 * 2 {
-            self.buffer[i] = 0;
+    /// Sets the brightness with the master contrast current, from 0 to 15.
+    ///
+    /// The current of the three colours is scaled by `(level + 1) / 16`.
+    pub fn set_brightness(&mut self, level: u8) -> Result<(), Error<DC::Error, SPI::Error>> {
+        self.send_command(Command::MasterContrast(level))?;
+        self.config = self.config.master_contrast(level);
+        Ok(())
+    }
+
+    /// Sets the contrast current of the red, green and blue channels, to balance the colours.
+    pub fn set_channel_contrast(&mut self, red: u8, green: u8, blue: u8) -> Result<(), Error<DC::Error, SPI::Error>> {
+        self.config = self.config.channel_contrast(red, green, blue);
+        self.send_command(self.config.contrast_command())
+    }
+
+    /// Clears the buffer to black, without sending it.
+    ///
+    /// `DrawTarget::clear` fills it with any colour.
+    pub fn clear_buffer(&mut self) -> Result<(), Error<DC::Error, SPI::Error>> {
+        let len = self.buffer_len();
+        self.buffer[..len].fill(0);
+        Ok(())
+    }
+}
+
+impl<SPI, DC, RST> DrawTarget for Ssd1351<SPI, DC, RST>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+    RST: OutputPin,
+{
+    type Color = Rgb565;
+    type Error = Error<DC::Error, SPI::Error>;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bounding_box = self.bounding_box();
+        let width = usize::from(self.config.width);
+        for Pixel(point, colour) in pixels {
+            // ignore out-of-bounds drawing
+            if !bounding_box.contains(point) {
+                continue;
+            }
+            let index = (point.x as usize + point.y as usize * width) * 2;
+            let colour = self.config.byte_order.bytes(RawU16::from(colour).into_inner());
+            self.buffer[index..index + 2].copy_from_slice(&colour);
+        }
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let area = area.intersection(&self.bounding_box());
+        let bottom_right = match area.bottom_right() {
+            Some(point) => point,
+            None => return Ok(()),
+        };
+
+        let colour = self.config.byte_order.bytes(RawU16::from(color).into_inner());
+        let width = usize::from(self.config.width);
+        let (left, right) = (area.top_left.x as usize, bottom_right.x as usize);
+        for row in area.top_left.y as usize..=bottom_right.y as usize {
+            let start = (row * width + left) * 2;
+            let end = (row * width + right + 1) * 2;
+            for pixel in self.buffer[start..end].chunks_exact_mut(2) {
+                pixel.copy_from_slice(&colour);
+            }
         }
         Ok(())
     }
 }
+
+impl<SPI, DC, RST> OriginDimensions for Ssd1351<SPI, DC, RST>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+    RST: OutputPin,
+{
+    fn size(&self) -> Size {
+        // Only the pixels held by the buffer can be drawn
+        let width = u32::from(self.config.width);
+        let height = (self.buffer_len() / 2) as u32 / width.max(1);
+        Size::new(width, height)
+    }
+}
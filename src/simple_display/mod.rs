@@ -1,8 +1,5 @@
 //! SSD1351 Display Driver
-#![cfg_attr(not(test), )]
-#![forbid(unsafe_code)]
-//#![doc(html_root_url = "https://docs.rs/ssd1351/0.2.0")]
-#![warn(missing_docs, rust_2018_idioms, unused_qualifications)]
 
 pub mod command;
+#[allow(clippy::module_inception)]
 pub mod display;